@@ -0,0 +1,255 @@
+/*
+Engine matches: two engine executables run as subprocesses and play games over the `protocol`, the
+controller referees every move through `Field`, keeps the clocks (`clock::TimeControl` for each side)
+and writes the games as `record::GameRecord`s.
+
+Before every `go` the engine gets the time left on its clock: the main time, or the byo-yomi period after
+it. A side loses at once when its time runs out, when it answers with an illegal move or not at all, or
+when its process fails; the record then has `[Result "*"]` and a `Termination` tag with the reason.
+The engines swap the colours every game, black moves first. The rules of a variant are written as the
+`Rules` tag of the record, for misère the engines get `go <ms> misere`.
+*/
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::point::{Point, move_from_string};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::game::possible_movement;
+use crate::rules::Rules;
+use crate::engine::Goal;
+use crate::clock::{Clock, SystemClock, TimeControl};
+use crate::record::{GameRecord, RecordMove};
+use crate::protocol::{Command, HANDSHAKE};
+
+
+//how long an engine may take for the answers besides `go`
+const ANSWER_MS: u64 = 5000;
+//how long an engine may take to exit after `quit`
+const QUIT_MS: u64 = 1000;
+
+
+//an engine running as a subprocess
+pub struct EngineProcess {
+    child: Child,
+    stdin: ChildStdin,
+    //the lines of its stdout, read by a thread
+    lines: Receiver<String>,
+    name: String,
+}
+
+impl EngineProcess {
+    //the command line is split on whitespace: the program and its arguments; the handshake is made here
+    pub fn spawn(command: &str) -> Result<EngineProcess, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("the engine command is empty")?;
+        let mut child = Process::new(program).args(words)
+            .stdin(Stdio::piped()).stdout(Stdio::piped())
+            .spawn().map_err(|e| format!("cannot start '{}': {}", command, e))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut engine = EngineProcess { child, stdin, lines, name: command.to_string() };
+        //a program which exits at once may close the pipe before the handshake is written, what it has
+        //printed is read anyway
+        let sent = engine.send(&Command::Hello);
+        let answer = engine.receive(&Command::Hello, ANSWER_MS)
+            .map_err(|e| format!("'{}' does not speak the protocol: {}", command, e))?;
+        match (answer.strip_prefix(HANDSHAKE), sent) {
+            (Some(name), Ok(())) if !name.trim().is_empty() => engine.name = name.trim().to_string(),
+            (Some(_), Ok(())) => {}
+            (Some(_), Err(e)) => return Err(format!("'{}' does not speak the protocol: {}", command, e)),
+            (None, _) => return Err(format!("'{}' does not speak the protocol, it answered '{}'", command, answer)),
+        }
+        Ok(engine)
+    }
+
+    //from the handshake, the command line if the engine has not told it
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&mut self, command: &Command) -> Result<(), String> {
+        writeln!(self.stdin, "{}", command).and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("cannot write to the engine: {}", e))
+    }
+
+    fn receive(&self, command: &Command, timeout_ms: u64) -> Result<String, String> {
+        match self.lines.recv_timeout(Duration::from_millis(timeout_ms)) {
+            Ok(line) => Ok(line.trim().to_string()),
+            Err(RecvTimeoutError::Timeout) => Err(format!("no answer to '{}' in {} ms", command, timeout_ms)),
+            Err(RecvTimeoutError::Disconnected) => Err("the engine has exited".to_string()),
+        }
+    }
+
+    //the answer line to the command, in timeout_ms
+    fn request(&mut self, command: &Command, timeout_ms: u64) -> Result<String, String> {
+        self.send(command)?;
+        self.receive(command, timeout_ms)
+    }
+
+    //a command answered with `ok`; a late answer to `go` of the game lost on time is skipped
+    fn tell(&mut self, command: &Command) -> Result<(), String> {
+        self.send(command)?;
+        loop {
+            match self.receive(command, ANSWER_MS)? {
+                answer if answer == "ok" => return Ok(()),
+                answer if answer.starts_with("move ") => {}
+                answer => return Err(format!("'{}' is answered with '{}'", command, answer)),
+            }
+        }
+    }
+}
+
+impl Drop for EngineProcess {
+    fn drop(&mut self) {
+        let _ = self.send(&Command::Quit);
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(QUIT_MS) {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct MatchSettings {
+    pub games: u32,
+    pub time: TimeControl,
+    //the start position, the winner (misère)
+    pub rules: Rules,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings { games: 2, time: TimeControl::SuddenDeath { main_ms: 60_000 }, rules: Rules::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    pub record: GameRecord,
+    //None for a draw
+    pub winner: Option<OccupyType>,
+    //why the game has ended early, None for the end of the game
+    pub termination: Option<String>,
+}
+
+
+//as the tags of the record
+fn side(bw: OccupyType) -> &'static str {
+    if bw == OccupyType::White { "White" } else { "Black" }
+}
+
+//the move of the engine, Err is the reason why the side has lost
+fn engine_move(engine: &mut EngineProcess, clock: &mut Clock<SystemClock>, f: &Field, bw: OccupyType, goal: Goal)
+    -> Result<(Point, u64), String> {
+    //the engine may think until its clock flags, in byo-yomi the periods follow the main time
+    let left = clock.until_flagged(bw);
+    clock.start(bw);
+    let answer = engine.request(&Command::Go(left, goal), left);
+    let used = clock.stop();
+    if clock.side(bw).is_flagged() {
+        return Err(format!("{} loses on time", side(bw)));
+    }
+    let answer = answer.map_err(|e| format!("{}: {}", side(bw), e))?;
+    let mv = answer.strip_prefix("move ").and_then(|m| move_from_string(m.trim()))
+        .ok_or_else(|| format!("{} answered '{}' to go", side(bw), answer))?;
+    match mv {
+        Some(p) if f.is_valid_move(&p, bw).0 => Ok((p, used)),
+        _ => Err(format!("{} played the illegal move '{}'", side(bw), answer)),
+    }
+}
+
+//the command to both engines, Err is the side which has failed and the reason
+fn tell_both(black: &mut EngineProcess, white: &mut EngineProcess, command: &Command) -> Result<(), (OccupyType, String)> {
+    black.tell(command).map_err(|e| (OccupyType::Black, format!("Black: {}", e)))?;
+    white.tell(command).map_err(|e| (OccupyType::White, format!("White: {}", e)))
+}
+
+//the moves of the game until its end, Err is the side which has lost early and the reason
+fn play_moves(black: &mut EngineProcess, white: &mut EngineProcess, settings: &MatchSettings, f: &mut Field, record: &mut GameRecord)
+    -> Result<(), (OccupyType, String)> {
+    let mut bw = OccupyType::Black;
+    tell_both(black, white, &Command::Position(f.clone(), bw))?;
+    let mut clock = Clock::new(settings.time, SystemClock::new());
+    while possible_movement(bw, f) || possible_movement(OccupyType::get_opposite_type(bw), f) {
+        //the passes are played by the controller
+        let mut m = RecordMove::new(None);
+        if possible_movement(bw, f) {
+            let engine = if bw == OccupyType::Black { &mut *black } else { &mut *white };
+            let (p, used) = engine_move(engine, &mut clock, f, bw, settings.rules.goal()).map_err(|e| (bw, e))?;
+            f.move_in_game(&p, bw);
+            m = RecordMove { mv: Some(p), time_ms: Some(used as u32), ..Default::default() };
+        }
+        tell_both(black, white, &Command::Play(m.mv))?;
+        record.moves.push(m);
+        bw = OccupyType::get_opposite_type(bw);
+    }
+    Ok(())
+}
+
+//a game from the start position of the rules, black moves first
+pub fn play_game(black: &mut EngineProcess, white: &mut EngineProcess, settings: &MatchSettings, round: u32) -> GameResult {
+    let mut f = settings.rules.field();
+    let mut record = GameRecord::new();
+    record.set_tag("Event", "rustversi match");
+    record.set_tag("Round", &round.to_string());
+    record.set_tag("Black", black.name());
+    record.set_tag("White", white.name());
+    record.set_tag("TimeControl", &settings.time.to_string());
+    if !settings.rules.is_standard() {
+        record.set_tag("Rules", &settings.rules.to_string());
+        record.set_start(&f, OccupyType::Black);
+    }
+    match play_moves(black, white, settings, &mut f, &mut record) {
+        Ok(()) => {
+            record.set_tag("Result", &GameRecord::result_of(&f));
+            GameResult { record, winner: settings.rules.winner(&f), termination: None }
+        }
+        Err((loser, reason)) => {
+            record.set_tag("Result", "*");
+            record.set_tag("Termination", &reason);
+            GameResult { record, winner: Some(OccupyType::get_opposite_type(loser)), termination: Some(reason) }
+        }
+    }
+}
+
+//`settings.games` games, the first engine is black in the odd rounds; `report` is called after every game
+pub fn play_match<F>(first: &mut EngineProcess, second: &mut EngineProcess, settings: &MatchSettings, mut report: F) -> Vec<GameResult>
+    where F: FnMut(u32, &GameResult) {
+    let mut results = vec![];
+    for round in 1..=settings.games {
+        let (black, white) = if round % 2 == 1 { (&mut *first, &mut *second) } else { (&mut *second, &mut *first) };
+        let result = play_game(black, white, settings, round);
+        report(round, &result);
+        results.push(result);
+    }
+    results
+}
+
+//the points of the first engine of `play_match`: 1 for a win, 0.5 for a draw
+pub fn first_points(results: &[GameResult]) -> f32 {
+    results.iter().enumerate().map(|(i, r)| {
+        let first = if i % 2 == 0 { OccupyType::Black } else { OccupyType::White };
+        match r.winner {
+            Some(bw) if bw == first => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }).sum()
+}
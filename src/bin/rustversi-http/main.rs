@@ -0,0 +1,58 @@
+use rustversi::http::{HttpServer, DEFAULT_THREADS};
+
+const USAGE: &str = "usage: rustversi-http [--listen <addr>] [--threads <n>]
+
+Serves the engine over HTTP, see the `http` module for the endpoints.
+  --listen <addr>   the address to listen on, 0.0.0.0:8080 by default
+  --threads <n>     the number of the threads serving the requests, 4 by default";
+const DEFAULT_LISTEN: &str = "0.0.0.0:8080";
+
+
+struct Options {
+    listen: String,
+    threads: usize,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { listen: DEFAULT_LISTEN.to_string(), threads: DEFAULT_THREADS };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--listen" => options.listen = value()?.to_string(),
+            "--threads" => {
+                let value = value()?;
+                options.threads = value.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| format!("wrong number of threads '{}'", value))?;
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("error: {}\n{}", msg, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let server = match HttpServer::bind(&options.listen, options.threads) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: cannot listen on {}: {}", options.listen, e);
+            std::process::exit(1);
+        }
+    };
+    if let Ok(addr) = server.local_addr() {
+        println!("listening on http://{} with {} threads", addr, options.threads);
+    }
+    server.run();
+}
@@ -0,0 +1,49 @@
+use rustversi::server::Server;
+
+const USAGE: &str = "usage: rustversi-server [--listen <addr>]
+
+Hosts games over WebSocket, see the `server` module for the JSON messages.
+  --listen <addr>   the address to listen on, 0.0.0.0:9001 by default";
+const DEFAULT_LISTEN: &str = "0.0.0.0:9001";
+
+
+fn parse_listen(args: &[String]) -> Result<String, String> {
+    let mut listen = DEFAULT_LISTEN.to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--listen" => listen = iter.next().ok_or_else(|| format!("{} needs a value", arg))?.to_string(),
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok(listen)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let listen = match parse_listen(&args) {
+        Ok(listen) => listen,
+        Err(msg) => {
+            eprintln!("error: {}\n{}", msg, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let server = match Server::bind(&listen) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: cannot listen on {}: {}", listen, e);
+            std::process::exit(1);
+        }
+    };
+    if let Ok(addr) = server.local_addr() {
+        println!("listening on ws://{}", addr);
+    }
+    if let Err(e) = server.run() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,655 @@
+use rustversi::point::{self, Point};
+use rustversi::occupytype::OccupyType;
+use rustversi::field::{Field, FieldAction};
+use rustversi::game::possible_movement;
+use rustversi::engine::{Engine, Goal};
+use rustversi::parser::parse_board;
+use rustversi::wthor::WthorReader;
+use rustversi::diagram::{self, Diagram};
+use rustversi::render::Style;
+use rustversi::rules::Rules;
+use rustversi::posdb::{self, PositionDb, Query};
+use rustversi::obf;
+use rustversi::record::GameRecord;
+use rustversi::multi::{self, MultiGame, MultiField};
+use rustversi::protocol::{self, EngineSession};
+use rustversi::arena::{self, EngineProcess, MatchSettings};
+use rustversi::clock::TimeControl;
+use rustversi::puzzle::{self, Answer, Puzzle, PuzzleSession};
+use std::io::BufRead;
+
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+//the interactive game
+pub const EXIT_ABORTED: i32 = 3;
+pub const EXIT_WHITE_WINS: i32 = 10;
+pub const EXIT_BLACK_WINS: i32 = 11;
+pub const EXIT_DRAW: i32 = 12;
+
+pub const USAGE: &str = "usage:
+  rustversi [options]                         interactive game
+      --mode hvc|hvh|cvc                      human vs computer, hot-seat or computer vs computer
+      --white <engine>, --black <engine>      computer player of the side (default: corner)
+      --delay <ms>                            pause before every computer move
+      --tui                                   full-screen terminal UI (`tui` feature)
+      --resume <file>                         continue a game saved with `save <file>`
+      --style <style>                         how the board is printed (default: classic)
+      --rules <rules>                         the start position and handicap (default: standard)
+      --time <control>                        game clocks: `5m` sudden death, `5m+3s` increment or
+                                              `5m/3x30s` byo-yomi (3 periods of 30s after the main time)
+  rustversi bestmove <board> <color> [engine] [--misere]
+                                              prints `x y` of the computer move or `pass`,
+                                              playing for the fewest discs with --misere
+  rustversi legal <board> <color>             prints `x y flips` for every legal move
+  rustversi apply <board> --to-move <color> <moves...>
+                                              plays `x,y` or `pass` moves, the color first (the game
+                                              starts with white, records and OBF with black), prints
+                                              the new board and the side to move (`-` when over)
+  rustversi show <board> [--style <style>]    prints the board and the score
+  rustversi wthor <file.wtb>                  prints `tournament black white score moves` for every game
+  rustversi render <board> [render options]   SVG diagram of the board, to stdout by default
+      --output <file>                         writes the file, PNG for `.png` (`png` feature)
+      --no-coordinates, --size <px>           hides the coordinates, the cell size (default 40)
+      --last <x,y>, --legal <color>           marks the last move, the legal moves of the side
+      --moves <x,y x,y ...>                   numbers the moves on the board (one argument)
+      --arrow <x,y:x,y>                       draws an arrow, may be repeated
+  rustversi db <file> add-game <moves...>     adds the positions of a game from the start (black first),
+                                              the file is created if it does not exist
+  rustversi db <file> import <file>           adds the games of a `.wtb` file or a game record,
+                                              the positions of an `.obf` file
+  rustversi db <file> query [query options]   prints the matching positions as `<board> <side> <count> <moves>`
+      --cell <a1=color>                       the cell is black, white or empty (`-`), may be repeated
+      --discs <n[-m]>, --black <n[-m]>, --white <n[-m]>
+                                              the number of all, black, white discs
+      --to-move <color>, --moves <f5 d6 ...>  the side to move, the game starts with the moves
+      --limit <n>                             prints the first n positions
+  rustversi db <file> stats                   the number of positions by the number of discs
+  rustversi multi <players> [--size <n>]      a game of 2..4 computer players (`#`, `O`, `+`, `%`) on an n x n
+                                              board (default 8, 9, 10), prints the moves, the board and the scores
+  rustversi engine [engine] [--misere]        answers the engine protocol on stdin/stdout (see src/protocol.rs)
+  rustversi match <command> <command> [match options]
+                                              plays games between two engine executables speaking the
+                                              protocol, e.g. `rustversi engine search:2` (one argument each)
+      --games <n>                             the number of games, the colours are swapped (default 2)
+      --time <control>                        the clock of each side as `--time` (default 1m)
+      --rules <rules>                         the start position, the winner of misère
+      --records <dir>                         writes the games as records `game-<n>.rec` to the dir
+  rustversi puzzle <file> [--number <n>] [--style <style>]
+                                              presents the puzzles of the file (see src/puzzle.rs) and
+                                              grades the answers: a move (`f5` or `x,y`), `pass`, `skip`
+                                              or `quit`; --number the n-th puzzle only
+  rustversi puzzle <file> --verify            checks with the engine that every puzzle is sound, prints
+                                              `<n> ok` or `<n> <error>`, fails if one is not
+
+  <board> is `start`, `start:<rules>` or a board in any notation of the parser: the 64-char string of
+          `#`, `O` and ` ` (row 8 first), 64 chars of `X`/`O`/`-`, a multi-line grid, ...
+  <color> is `#`/`X`/`black`/`b` or `O`/`white`/`w`
+  <move> of `apply`/`db` is `x,y` or `pass`, `db` also takes `f5`-like moves (`a1` is 1,8)
+  <engine> is `random`, `greedy`, `corner`, `search` or `search:<depth>`
+  <rules> is `standard`, `parallel` or `custom:<64 cells of X/O/- and @ for blocked>`, optionally
+          followed by `,handicap:<n>[:<color>]`: n corners (a1, h8, h1, a8) for the color (black by
+          default), `,blocked:<a1+d3...>`: cells no disc may occupy, `,shape:<shape>`: the board shape
+          (`octagon[:<n>]`, `rect:<w>x<h>` or `mask:<64 cells of 1/0>`) and `,misere`: the fewest discs win
+  <style> is `classic`, `compact`, `unicode`, `annotated` (legal moves of the side to move, black
+          for `show`) or `annotated:<color>`
+
+  exit codes: 0 ok, 1 invalid board or move, 2 wrong usage,
+              3 game aborted (quit or closed input), 10 white wins, 11 black wins, 12 draw";
+
+
+//the winner of `Rules::winner`, None for a draw
+pub fn result_code(winner: Option<OccupyType>) -> i32 {
+    match winner {
+        Some(OccupyType::White) => EXIT_WHITE_WINS,
+        Some(_) => EXIT_BLACK_WINS,
+        None => EXIT_DRAW,
+    }
+}
+
+
+enum CommandError {
+    Usage(String),
+    Failed(String),
+}
+
+
+fn board_arg(s: &str) -> Result<Field, CommandError> {
+    if s == "start" {
+        let mut f = Field::new();
+        f.init();
+        return Ok(f);
+    }
+    if let Some(rules) = s.strip_prefix("start:") {
+        return Rules::from_string(rules).map(|r| r.field()).map_err(CommandError::Failed);
+    }
+    parse_board(s).map_err(|e| CommandError::Failed(format!("wrong board: {}", e)))
+}
+
+fn parse_color(s: &str) -> Result<OccupyType, CommandError> {
+    match s.to_lowercase().as_str() {
+        "#" | "x" | "black" | "b" => Ok(OccupyType::Black),
+        "o" | "white" | "w" => Ok(OccupyType::White),
+        _ => Err(CommandError::Failed(format!("unknown color '{}'", s)))
+    }
+}
+
+fn expect_args(args: &[String], n: usize) -> Result<(), CommandError> {
+    if args.len() != n {
+        return Err(CommandError::Usage(format!("expected {} arguments, got {}", n, args.len())));
+    }
+    Ok(())
+}
+
+
+fn cmd_bestmove(args: &[String]) -> Result<(), CommandError> {
+    let (args, goal) = match args.split_last() {
+        Some((last, rest)) if last == "--misere" => (rest, Goal::FewestDiscs),
+        _ => (args, Goal::MostDiscs),
+    };
+    if args.len() != 3 {
+        expect_args(args, 2)?;
+    }
+    let f = board_arg(&args[0])?;
+    let bw = parse_color(&args[1])?;
+    let engine = match args.get(2) {
+        Some(s) => Engine::from_string(s).ok_or_else(|| CommandError::Failed(format!("unknown engine '{}'", s)))?,
+        None => Engine::default(),
+    };
+    match engine.get_move_goal(bw, &f, goal) {
+        Some(p) => println!("{} {}", p.x(), p.y()),
+        None => println!("pass"),
+    }
+    Ok(())
+}
+
+fn cmd_legal(args: &[String]) -> Result<(), CommandError> {
+    expect_args(args, 2)?;
+    let f = board_arg(&args[0])?;
+    let bw = parse_color(&args[1])?;
+    for (p, n) in f.get_list_of_moves(bw) {
+        println!("{} {} {}", p.x(), p.y(), n);
+    }
+    Ok(())
+}
+
+fn cmd_apply(args: &[String]) -> Result<(), CommandError> {
+    if args.is_empty() {
+        return Err(CommandError::Usage("board is missing".to_string()));
+    }
+    //the side is not guessed: the game starts with white, records and OBF with black
+    let (mut bw, moves) = match &args[1..] {
+        [option, color, moves @ ..] if option == "--to-move" => (parse_color(color)?, moves),
+        [option] if option == "--to-move" => return Err(CommandError::Usage("--to-move needs a color".to_string())),
+        _ => return Err(CommandError::Usage("--to-move <color> is missing".to_string())),
+    };
+    let mut f = board_arg(&args[0])?;
+
+    for m in moves {
+        if !possible_movement(bw, &f) {
+            //the side has to pass, an explicit `pass` is optional
+            bw = OccupyType::get_opposite_type(bw);
+            if m == "pass" {
+                continue;
+            }
+        } else if m == "pass" {
+            return Err(CommandError::Failed(format!("{} cannot pass, it has a legal move", bw)));
+        }
+        let p = Point::from_string(m)
+            .ok_or_else(|| CommandError::Failed(format!("cannot parse move '{}'", m)))?;
+        let (valid, _) = f.is_valid_move(&p, bw);
+        if !valid {
+            return Err(CommandError::Failed(format!("{} is not a valid move for {}", p, bw)));
+        }
+        f.move_in_game(&p, bw);
+        bw = OccupyType::get_opposite_type(bw);
+    }
+
+    println!("{}", f.serialize());
+    if possible_movement(bw, &f) {
+        println!("{}", bw);
+    } else if possible_movement(OccupyType::get_opposite_type(bw), &f) {
+        println!("{}", OccupyType::get_opposite_type(bw));
+    } else {
+        println!("-");
+    }
+    Ok(())
+}
+
+fn cmd_show(args: &[String]) -> Result<(), CommandError> {
+    let style = match args {
+        [_, option, value] if option == "--style" =>
+            Style::from_string(value).ok_or_else(|| CommandError::Failed(format!("unknown style '{}'", value)))?,
+        _ => {
+            expect_args(args, 1)?;
+            Style::default()
+        }
+    };
+    let f = board_arg(&args[0])?;
+    println!("{}", style.render(&f, OccupyType::Black));
+    let (w, b) = f.get_score_wb();
+    println!("Score {} vs {} -- {}:{}", OccupyType::Black, OccupyType::White, b, w);
+    Ok(())
+}
+
+fn cmd_wthor(args: &[String]) -> Result<(), CommandError> {
+    expect_args(args, 1)?;
+    let file = std::fs::File::open(&args[0])
+        .map_err(|e| CommandError::Failed(format!("cannot read {}: {}", args[0], e)))?;
+    let reader = WthorReader::new(std::io::BufReader::new(file)).map_err(CommandError::Failed)?;
+    for game in reader {
+        let game = game.map_err(CommandError::Failed)?;
+        let moves: String = game.moves.iter().map(|p| p.to_algebraic()).collect();
+        println!("{} {} {} {} {}", game.tournament, game.black, game.white, game.score, moves);
+    }
+    Ok(())
+}
+
+fn point_arg(s: &str) -> Result<Point, CommandError> {
+    Point::from_string(s).ok_or_else(|| CommandError::Failed(format!("cannot parse point '{}'", s)))
+}
+
+fn cmd_render(args: &[String]) -> Result<(), CommandError> {
+    if args.is_empty() {
+        return Err(CommandError::Usage("board is missing".to_string()));
+    }
+    let f = board_arg(&args[0])?;
+    let mut d = Diagram::new();
+    let mut output = None;
+    let mut rest = args[1..].iter();
+    while let Some(option) = rest.next() {
+        if option == "--no-coordinates" {
+            d.coordinates = false;
+            continue;
+        }
+        let mut value = || rest.next().ok_or_else(|| CommandError::Usage(format!("{} needs a value", option)));
+        match option.as_str() {
+            "--output" => output = Some(value()?.clone()),
+            "--size" => {
+                let value = value()?;
+                d.cell_size = value.parse().ok().filter(|size| *size >= 8 && *size <= 200)
+                    .ok_or_else(|| CommandError::Failed(format!("wrong size '{}', expected 8..200", value)))?;
+            }
+            "--last" => d.last_move = Some(point_arg(value()?)?),
+            "--legal" => d.legal_moves = Some(parse_color(value()?)?),
+            "--moves" => {
+                let moves = value()?.split_whitespace().map(|m| point_arg(m).map(Some)).collect::<Result<Vec<_>, _>>()?;
+                d.number_moves(&moves);
+            }
+            "--arrow" => {
+                let value = value()?;
+                let arrow = diagram::parse_arrow(value)
+                    .ok_or_else(|| CommandError::Failed(format!("cannot parse arrow '{}', expected x,y:x,y", value)))?;
+                d.arrows.push(arrow);
+            }
+            _ => return Err(CommandError::Usage(format!("unknown option '{}'", option))),
+        }
+    }
+
+    match output {
+        None => print!("{}", d.to_svg(&f)),
+        Some(path) => {
+            let data = if path.to_lowercase().ends_with(".png") { render_png(&d, &f)? } else { d.to_svg(&f).into_bytes() };
+            std::fs::write(&path, data).map_err(|e| CommandError::Failed(format!("cannot write {}: {}", path, e)))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+fn render_png(d: &Diagram, f: &Field) -> Result<Vec<u8>, CommandError> {
+    Ok(d.to_png(f))
+}
+
+#[cfg(not(feature = "png"))]
+fn render_png(_d: &Diagram, _f: &Field) -> Result<Vec<u8>, CommandError> {
+    Err(CommandError::Failed("PNG export needs the `png` feature: cargo build --features png".to_string()))
+}
+
+
+fn move_args(args: &[String]) -> Result<Vec<Option<Point>>, CommandError> {
+    args.iter().flat_map(|a| a.split_whitespace())
+        .map(|m| posdb::parse_move(m).ok_or_else(|| CommandError::Failed(format!("cannot parse move '{}'", m))))
+        .collect()
+}
+
+//"12" or "10-20"
+fn range_arg(s: &str) -> Result<std::ops::RangeInclusive<u8>, CommandError> {
+    let wrong = || CommandError::Failed(format!("wrong range '{}', expected n or n-m", s));
+    let (min, max) = s.split_once('-').unwrap_or((s, s));
+    let min = min.trim().parse::<u8>().map_err(|_| wrong())?;
+    let max = max.trim().parse::<u8>().map_err(|_| wrong())?;
+    if min > max {
+        return Err(CommandError::Failed(format!("wrong range '{}', {} is more than {}", s, min, max)));
+    }
+    Ok(min..=max)
+}
+
+fn cell_arg(s: &str) -> Result<(Point, OccupyType), CommandError> {
+    let wrong = || CommandError::Failed(format!("wrong cell '{}', expected e.g. a1=# or a1=-", s));
+    let (cell, color) = s.split_once('=').ok_or_else(wrong)?;
+    let p = posdb::parse_move(cell).flatten().ok_or_else(wrong)?;
+    let bw = match color {
+        "-" | "." | "empty" => OccupyType::Empty,
+        c => parse_color(c)?,
+    };
+    Ok((p, bw))
+}
+
+fn db_import(db: &mut PositionDb, path: &str) -> Result<usize, CommandError> {
+    let failed = |e: String| CommandError::Failed(format!("{}: {}", path, e));
+    let lower = path.to_lowercase();
+    if lower.ends_with(".wtb") {
+        let file = std::fs::File::open(path).map_err(|e| failed(e.to_string()))?;
+        let reader = WthorReader::new(std::io::BufReader::new(file)).map_err(failed)?;
+        let mut added = 0;
+        for game in reader {
+            let moves: Vec<Option<Point>> = game.map_err(failed)?.moves.into_iter().map(Some).collect();
+            added += db.add_game(&moves, OccupyType::Black).map_err(failed)?;
+        }
+        return Ok(added);
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| failed(e.to_string()))?;
+    if lower.ends_with(".obf") {
+        let records = obf::read_all(&text).map_err(|e| failed(e.to_string()))?;
+        return Ok(records.iter().filter(|r| db.add(&r.field, r.to_move, &[])).count());
+    }
+    let record = GameRecord::from_string(&text).map_err(|e| failed(e.to_string()))?;
+    let (start, first) = record.start().map_err(failed)?;
+    let moves: Vec<Option<Point>> = record.moves.iter().map(|m| m.mv).collect();
+    db.add_game_from(&start, first, &moves).map_err(failed)
+}
+
+fn db_query(db: &PositionDb, args: &[String]) -> Result<(), CommandError> {
+    let mut q = Query::new();
+    let mut limit = usize::MAX;
+    let mut rest = args.iter();
+    while let Some(option) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| CommandError::Usage(format!("{} needs a value", option)));
+        match option.as_str() {
+            "--cell" => q.cells.push(cell_arg(value()?)?),
+            "--discs" => q.discs = Some(range_arg(value()?)?),
+            "--black" => q.black = Some(range_arg(value()?)?),
+            "--white" => q.white = Some(range_arg(value()?)?),
+            "--to-move" => q.to_move = Some(parse_color(value()?)?),
+            "--moves" => q.moves = move_args(std::slice::from_ref(value()?))?,
+            "--limit" => {
+                let value = value()?;
+                limit = value.parse().map_err(|_| CommandError::Failed(format!("wrong limit '{}'", value)))?;
+            }
+            _ => return Err(CommandError::Usage(format!("unknown option '{}'", option))),
+        }
+    }
+    for p in db.query(&q).into_iter().take(limit) {
+        println!("{}", p);
+    }
+    Ok(())
+}
+
+fn cmd_db(args: &[String]) -> Result<(), CommandError> {
+    if args.len() < 2 {
+        return Err(CommandError::Usage("db needs a file and a command".to_string()));
+    }
+    let path = &args[0];
+    let mut db = PositionDb::load(path).map_err(CommandError::Failed)?;
+    let rest = &args[2..];
+    match args[1].as_str() {
+        "add-game" => {
+            let added = db.add_game(&move_args(rest)?, OccupyType::Black).map_err(CommandError::Failed)?;
+            db.save(path).map_err(CommandError::Failed)?;
+            println!("{} new positions, {} in total", added, db.len());
+        }
+        "import" => {
+            expect_args(rest, 1)?;
+            let added = db_import(&mut db, &rest[0])?;
+            db.save(path).map_err(CommandError::Failed)?;
+            println!("{} new positions, {} in total", added, db.len());
+        }
+        "query" => db_query(&db, rest)?,
+        "stats" => {
+            expect_args(rest, 0)?;
+            let added: u32 = db.positions().iter().map(|p| p.count).sum();
+            println!("{} positions, added {} times", db.len(), added);
+            let mut by_discs = std::collections::BTreeMap::new();
+            for p in db.positions() {
+                let (w, b) = p.field.get_score_wb();
+                *by_discs.entry(w + b).or_insert(0) += 1;
+            }
+            for (discs, n) in by_discs {
+                println!("{} discs: {}", discs, n);
+            }
+        }
+        c => return Err(CommandError::Usage(format!("unknown db command '{}'", c))),
+    }
+    Ok(())
+}
+
+
+fn cmd_multi(args: &[String]) -> Result<(), CommandError> {
+    let (players, size) = match args {
+        [players] => (players, None),
+        [players, option, size] if option == "--size" => (players, Some(size)),
+        _ => return Err(CommandError::Usage("expected <players> [--size <n>]".to_string())),
+    };
+    let players = players.parse::<u8>()
+        .map_err(|_| CommandError::Failed(format!("wrong number of players '{}'", players)))?;
+    let size = match size {
+        Some(s) => s.parse::<u8>().map_err(|_| CommandError::Failed(format!("wrong board size '{}'", s)))?,
+        None => MultiField::default_size(players),
+    };
+    let mut game = MultiGame::with_size(size, players).map_err(CommandError::Failed)?;
+    while let Some(colour) = game.to_move() {
+        let (x, y) = multi::multi_computer_move(game.field(), colour).expect("the player to move has a move");
+        let n = game.play(x, y).map_err(CommandError::Failed)?;
+        println!("{} {},{} +{}", colour, x, y, n);
+    }
+    print!("{}", game.field());
+    let scores: Vec<String> = game.scores().iter().map(|s| s.to_string()).collect();
+    let colours: Vec<String> = game.field().colours().iter().map(|c| c.to_string()).collect();
+    let winners: Vec<String> = game.winners().iter().map(|c| c.to_string()).collect();
+    println!("Score {} -- {}", colours.join(" vs "), scores.join(":"));
+    println!("Winner {}", winners.join(" "));
+    Ok(())
+}
+
+fn cmd_engine(args: &[String]) -> Result<(), CommandError> {
+    let (args, goal) = match args.split_last() {
+        Some((last, rest)) if last == "--misere" => (rest, Goal::FewestDiscs),
+        _ => (args, Goal::MostDiscs),
+    };
+    let engine = match args {
+        [] => Engine::default(),
+        [s] => Engine::from_string(s).ok_or_else(|| CommandError::Failed(format!("unknown engine '{}'", s)))?,
+        _ => return Err(CommandError::Usage("expected [engine] [--misere]".to_string())),
+    };
+    let mut session = EngineSession::new(engine, goal);
+    let stdin = std::io::stdin();
+    protocol::serve(&mut session, stdin.lock(), std::io::stdout())
+        .map_err(|e| CommandError::Failed(e.to_string()))
+}
+
+fn cmd_match(args: &[String]) -> Result<(), CommandError> {
+    if args.len() < 2 {
+        return Err(CommandError::Usage("expected two engine commands".to_string()));
+    }
+    let mut settings = MatchSettings::default();
+    let mut records = None;
+    let mut rest = args[2..].iter();
+    while let Some(option) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| CommandError::Usage(format!("{} needs a value", option)));
+        match option.as_str() {
+            "--games" => {
+                let value = value()?;
+                settings.games = value.parse().ok().filter(|n| *n > 0)
+                    .ok_or_else(|| CommandError::Failed(format!("wrong number of games '{}'", value)))?;
+            }
+            "--time" => settings.time = TimeControl::from_string(value()?).map_err(CommandError::Failed)?,
+            "--rules" => settings.rules = Rules::from_string(value()?).map_err(CommandError::Failed)?,
+            "--records" => records = Some(value()?.clone()),
+            _ => return Err(CommandError::Usage(format!("unknown option '{}'", option))),
+        }
+    }
+    if let Some(dir) = &records {
+        std::fs::create_dir_all(dir).map_err(|e| CommandError::Failed(format!("{}: {}", dir, e)))?;
+    }
+
+    let mut first = EngineProcess::spawn(&args[0]).map_err(CommandError::Failed)?;
+    let mut second = EngineProcess::spawn(&args[1]).map_err(CommandError::Failed)?;
+    let mut written = Ok(());
+    let results = arena::play_match(&mut first, &mut second, &settings, |round, result| {
+        let tag = |name: &str| result.record.tag(name).unwrap_or_default().to_string();
+        //the winner by the rules, the Result tag counts the discs
+        let winner = match result.winner {
+            Some(OccupyType::Black) => "Black wins",
+            Some(_) => "White wins",
+            None => "draw",
+        };
+        print!("Round {}: {} vs {} {}, {}", round, tag("Black"), tag("White"), tag("Result"), winner);
+        match &result.termination {
+            Some(reason) => println!(" ({})", reason),
+            None => println!(),
+        }
+        if let (Some(dir), Ok(())) = (&records, &written) {
+            let path = std::path::Path::new(dir).join(format!("game-{}.rec", round));
+            written = std::fs::write(&path, result.record.to_string())
+                .map_err(|e| CommandError::Failed(format!("{}: {}", path.display(), e)));
+        }
+    });
+    written?;
+    let points = arena::first_points(&results);
+    println!("Score {} vs {} -- {}:{}", first.name(), second.name(), points, results.len() as f32 - points);
+    Ok(())
+}
+
+fn puzzle_move(s: &str) -> Option<Option<Point>> {
+    point::move_from_string(s).or_else(|| Point::from_string(s).map(Some))
+}
+
+//true if the puzzle is solved, None to quit
+fn solve_puzzle(p: &Puzzle, style: Style, input: &mut impl Iterator<Item = String>) -> Option<bool> {
+    let mut session = PuzzleSession::new(p);
+    while !session.is_over() {
+        println!("{}", style.render(session.field(), session.to_move()));
+        println!("{} to move, your move?", session.to_move());
+        let line = input.next()?;
+        let answer = match line.trim() {
+            "quit" => return None,
+            "skip" => return Some(false),
+            m => match puzzle_move(m) {
+                Some(mv) => session.answer(mv),
+                None => {
+                    println!("cannot parse move '{}'", m);
+                    continue;
+                }
+            },
+        };
+        match answer {
+            Answer::Illegal => println!("not a legal move, try again"),
+            Answer::Wrong(expected) => println!("Wrong, the solution is {}", point::move_to_string(expected)),
+            Answer::Correct(Some(reply)) => println!("Correct, the reply is {}", point::move_to_string(reply)),
+            Answer::Correct(None) => println!("Correct, solved"),
+        }
+    }
+    Some(session.is_solved())
+}
+
+fn cmd_puzzle(args: &[String]) -> Result<(), CommandError> {
+    let (path, options) = args.split_first().ok_or_else(|| CommandError::Usage("puzzle file is missing".to_string()))?;
+    let text = std::fs::read_to_string(path).map_err(|e| CommandError::Failed(format!("{}: {}", path, e)))?;
+    let puzzles = puzzle::read_all(&text).map_err(|e| CommandError::Failed(format!("{}: {}", path, e)))?;
+
+    if options.iter().any(|o| o == "--verify") {
+        expect_args(options, 1)?;
+        let mut unsound = 0;
+        for (i, p) in puzzles.iter().enumerate() {
+            match p.verify() {
+                Ok(()) => println!("{} ok", i + 1),
+                Err(e) => {
+                    println!("{} {}", i + 1, e);
+                    unsound += 1;
+                }
+            }
+        }
+        if unsound > 0 {
+            return Err(CommandError::Failed(format!("{} of {} puzzles are not sound", unsound, puzzles.len())));
+        }
+        return Ok(());
+    }
+
+    let mut number = None;
+    let mut style = Style::default();
+    let mut rest = options.iter();
+    while let Some(option) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| CommandError::Usage(format!("{} needs a value", option)));
+        match option.as_str() {
+            "--number" => {
+                let value = value()?;
+                number = Some(value.parse::<usize>().ok().filter(|n| (1..=puzzles.len()).contains(n))
+                    .ok_or_else(|| CommandError::Failed(format!("there is no puzzle '{}'", value)))?);
+            }
+            "--style" => {
+                let value = value()?;
+                style = Style::from_string(value).ok_or_else(|| CommandError::Failed(format!("unknown style '{}'", value)))?;
+            }
+            _ => return Err(CommandError::Usage(format!("unknown option '{}'", option))),
+        }
+    }
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock().lines().map_while(Result::ok);
+    let mut solved = 0;
+    let mut presented = 0;
+    for (i, p) in puzzles.iter().enumerate() {
+        if number.is_some_and(|n| n != i + 1) {
+            continue;
+        }
+        println!("Puzzle {}: {}", i + 1, if p.title.is_empty() { p.goal.to_string() } else { p.title.clone() });
+        presented += 1;
+        match solve_puzzle(p, style, &mut input) {
+            Some(true) => solved += 1,
+            Some(false) => {}
+            None => break,
+        }
+    }
+    println!("Solved {} of {}", solved, presented);
+    Ok(())
+}
+
+
+//None if the arguments are not a subcommand
+pub fn run(args: &[String]) -> Option<i32> {
+    let (name, rest) = args.split_first()?;
+    if name.starts_with("--") && name != "--help" {
+        return None;
+    }
+    let result = match name.as_str() {
+        "bestmove" => cmd_bestmove(rest),
+        "legal" => cmd_legal(rest),
+        "apply" => cmd_apply(rest),
+        "show" => cmd_show(rest),
+        "wthor" => cmd_wthor(rest),
+        "render" => cmd_render(rest),
+        "db" => cmd_db(rest),
+        "multi" => cmd_multi(rest),
+        "engine" => cmd_engine(rest),
+        "match" => cmd_match(rest),
+        "puzzle" => cmd_puzzle(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(CommandError::Usage(format!("unknown command '{}'", name))),
+    };
+    let code = match result {
+        Ok(()) => EXIT_OK,
+        Err(CommandError::Usage(msg)) => {
+            eprintln!("error: {}\n{}", msg, USAGE);
+            EXIT_USAGE
+        }
+        Err(CommandError::Failed(msg)) => {
+            eprintln!("error: {}", msg);
+            EXIT_ERROR
+        }
+    };
+    Some(code)
+}
@@ -0,0 +1,501 @@
+use rustversi::point::{Point};
+use rustversi::occupytype::OccupyType;
+use rustversi::field::{Field, FieldAction};
+use rustversi::game::possible_movement;
+use rustversi::engine::Engine;
+use rustversi::savegame::SavedGame;
+use rustversi::render::Style;
+use rustversi::rules::Rules;
+use rustversi::clock::{Clock, SystemClock, TimeControl};
+use std::time::Duration;
+
+mod commands;
+#[cfg(feature = "tui")]
+mod tui;
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Mode {
+    HumanVsComputer,
+    HumanVsHuman,
+    ComputerVsComputer,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Side {
+    Human,
+    Computer(Engine),
+}
+
+struct Players {
+    mode: Mode,
+    white: Side,
+    black: Side,
+    delay: Duration,
+    //how the board is printed
+    style: Style,
+}
+
+impl Players {
+    fn get(&self, bw: OccupyType) -> Side {
+        if bw == OccupyType::White { self.white } else { self.black }
+    }
+
+    fn name(&self, bw: OccupyType) -> String {
+        match (self.mode, self.get(bw)) {
+            (Mode::HumanVsComputer, Side::Human) => "Player".to_string(),
+            (Mode::HumanVsComputer, Side::Computer(_)) => "Computer".to_string(),
+            (_, Side::Human) => format!("Player {}", bw),
+            (_, Side::Computer(e)) => format!("Computer {} ({})", bw, e),
+        }
+    }
+}
+
+//the game in progress
+struct Game {
+    players: Players,
+    field: Field,
+    to_move: OccupyType,
+    //None for a pass
+    moves: Vec<Option<Point>>,
+    rules: Rules,
+    //None without a time control
+    clock: Option<Clock<SystemClock>>,
+}
+
+impl Game {
+    fn new(players: Players, rules: Rules) -> Game {
+        //white moves first
+        Game { players, field: rules.field(), to_move: OccupyType::White, moves: vec![], rules, clock: None }
+    }
+
+    fn from_saved(saved: SavedGame, delay: Duration, style: Style) -> Game {
+        let side = |player: Option<Engine>| match player {
+            Some(engine) => Side::Computer(engine),
+            None => Side::Human,
+        };
+        let mode = match (saved.white, saved.black) {
+            (None, None) => Mode::HumanVsHuman,
+            (Some(_), Some(_)) => Mode::ComputerVsComputer,
+            _ => Mode::HumanVsComputer,
+        };
+        let players = Players { mode, white: side(saved.white), black: side(saved.black), delay, style };
+        Game { players, field: saved.field, to_move: saved.to_move, moves: saved.moves, rules: saved.rules, clock: None }
+    }
+
+    fn to_saved(&self) -> SavedGame {
+        let player = |side: Side| match side {
+            Side::Computer(engine) => Some(engine),
+            Side::Human => None,
+        };
+        SavedGame {
+            field: self.field.clone(),
+            to_move: self.to_move,
+            white: player(self.players.white),
+            black: player(self.players.black),
+            moves: self.moves.clone(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    //the clock of the side to move starts running, the clocks are not saved
+    fn start_clock(&mut self, control: Option<TimeControl>) {
+        self.clock = control.map(|control| Clock::new(control, SystemClock::new()));
+        if let Some(clock) = &mut self.clock {
+            clock.start(self.to_move);
+        }
+    }
+
+    fn press_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.press();
+        }
+    }
+
+    //the side which has lost on time
+    fn flagged(&self) -> Option<OccupyType> {
+        self.clock.as_ref().and_then(|c| c.flagged())
+    }
+
+    //" in 2.5s" after a move with the clock
+    fn move_time(&self) -> String {
+        match self.clock.as_ref().and_then(|c| c.last_move_ms()) {
+            Some(ms) => format!(" in {}.{}s", ms / 1000, ms % 1000 / 100),
+            None => String::new(),
+        }
+    }
+
+    fn play_move(&mut self, p: &Point) -> u8 {
+        let changed = self.field.move_in_game(p, self.to_move);
+        self.press_clock();
+        self.moves.push(Some(*p));
+        self.to_move = OccupyType::get_opposite_type(self.to_move);
+        changed
+    }
+
+    fn pass(&mut self) {
+        self.press_clock();
+        self.moves.push(None);
+        self.to_move = OccupyType::get_opposite_type(self.to_move);
+    }
+}
+
+struct Options {
+    mode: Option<Mode>,
+    white: Engine,
+    black: Engine,
+    delay: Duration,
+    style: Style,
+    rules: Rules,
+    time: Option<TimeControl>,
+    tui: bool,
+    resume: Option<String>,
+}
+
+
+
+const PROMPT_HELP: &str = "  x y         -- move to column x, row y (1..8)
+  save <file> -- save the game
+  load <file> -- continue a saved game
+  resign      -- give up the game
+  quit        -- leave without a result
+  help        -- this text";
+
+
+//what the human has typed at the move prompt
+enum Action {
+    Move(Point),
+    Save(String),
+    Load(String),
+    Resign,
+    Quit,
+}
+
+//how the game loop has ended
+enum Outcome {
+    Over,
+    Resigned(OccupyType),
+    TimeOut(OccupyType),
+    Aborted,
+}
+
+
+//None when stdin is closed or broken
+fn read_input(prompt: &str) -> Option<String> {
+    println!("{}", prompt);
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+//None if the user quits
+fn choice<T: Copy>(prompt: &str, options: &[T]) -> Option<T> {
+    loop {
+        let line = read_input(prompt)?.to_lowercase();
+        if line == "quit" || line == "q" {
+            return None;
+        }
+        match line.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= options.len() => return Some(options[n - 1]),
+            _ => println!(" please type a number from 1 to {} or quit", options.len()),
+        }
+    }
+}
+
+fn beginning_choice() -> Option<OccupyType> {
+    let sel = choice(" O - 1, # - 2 ?", &[OccupyType::White, OccupyType::Black])?;
+    println!("Selected: {}", sel);
+    Some(sel)
+}
+
+fn mode_choice() -> Option<Mode> {
+    choice(" human vs computer - 1, human vs human - 2, computer vs computer - 3 ?",
+           &[Mode::HumanVsComputer, Mode::HumanVsHuman, Mode::ComputerVsComputer])
+}
+
+fn get_action() -> Action {
+    loop {
+        let line = match read_input(" x y ?") {
+            Some(line) => line,
+            None => return Action::Quit,
+        };
+        let (command, arg) = match line.find(char::is_whitespace) {
+            Some(i) => (line[..i].to_lowercase(), line[i..].trim()),
+            None => (line.to_lowercase(), ""),
+        };
+        match (command.as_str(), arg) {
+            ("", _) => continue,
+            ("quit", "") | ("q", "") => return Action::Quit,
+            ("resign", "") => return Action::Resign,
+            ("help", "") | ("?", "") => {
+                println!("{}", PROMPT_HELP);
+                continue;
+            }
+            ("save", "") | ("load", "") => {
+                println!(" {} needs a file name", command);
+                continue;
+            }
+            ("save", file) => return Action::Save(file.to_string()),
+            ("load", file) => return Action::Load(file.to_string()),
+            _ => {}
+        }
+        match Point::from_string(&line) {
+            Some(p) => return Action::Move(p),
+            None => println!(" '{}' -- is not a move, type `x y` with numbers from 1 to 8 or help", line),
+        }
+    }
+}
+
+//the action which ends the turn without a move, None after a move
+fn player_move(game: &mut Game) -> Option<Action> {
+    let bw = game.to_move;
+    loop {
+        let p = match get_action() {
+            Action::Move(p) => p,
+            Action::Save(file) => {
+                match game.to_saved().save(&file) {
+                    Ok(()) => println!(" saved to {}", file),
+                    Err(e) => println!(" {}", e),
+                }
+                continue;
+            }
+            action => return Some(action),
+        };
+        let (valid, _) = game.field.is_valid_move(&p, bw);
+        if !valid {
+            println!(" {} -- is not valid move", p);
+            continue;
+        }
+        let changed = game.play_move(&p);
+        println!("{} has moved to {}, +{} score{}", game.players.name(bw), p, changed + 1, game.move_time());
+        return None;
+    }
+}
+
+fn engine_move(game: &mut Game, engine: Engine) {
+    let bw = game.to_move;
+    let p = engine.get_move_goal(bw, &game.field, game.rules.goal()).expect("Computer has no move");
+    let changed = game.play_move(&p);
+    println!("{} has moved to {}, +{} score{}", game.players.name(bw), p, changed + 1, game.move_time());
+}
+
+
+fn print_field_and_score(game: &Game, win: bool) {
+    let players = &game.players;
+    let f = &game.field;
+    println!("{}", players.style.render(f, game.to_move));
+    let (w, b) = game.rules.score(f);
+    let (first, second) = match (players.mode, players.white) {
+        (Mode::HumanVsComputer, Side::Human) => (OccupyType::White, OccupyType::Black),
+        _ => (OccupyType::Black, OccupyType::White),
+    };
+    let score = |bw: OccupyType| if bw == OccupyType::White { w } else { b };
+
+    println!("Score {} vs {} -- {}:{}", players.name(first), players.name(second), score(first), score(second));
+    if let Some(clock) = &game.clock {
+        println!("Clock {} vs {} -- {} : {}", players.name(first), players.name(second), clock.side(first), clock.side(second));
+    }
+    if win {
+        match game.rules.winner(f) {
+            None => println!("DRAW"),
+            Some(bw) => println!("{} WINS", players.name(bw)),
+        }
+    }
+}
+
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        mode: None,
+        white: Engine::default(),
+        black: Engine::default(),
+        delay: Duration::from_millis(0),
+        style: Style::default(),
+        rules: Rules::new(),
+        time: None,
+        tui: false,
+        resume: None,
+    };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--tui" {
+            if !cfg!(feature = "tui") {
+                return Err("rustversi is built without the `tui` feature".to_string());
+            }
+            options.tui = true;
+            continue;
+        }
+        let mut value = || iter.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--mode" => {
+                let value = value()?;
+                options.mode = Some(match value.as_str() {
+                    "hvc" => Mode::HumanVsComputer,
+                    "hvh" => Mode::HumanVsHuman,
+                    "cvc" => Mode::ComputerVsComputer,
+                    _ => return Err(format!("unknown mode '{}'", value)),
+                });
+            }
+            "--white" | "--black" => {
+                let value = value()?;
+                let engine = Engine::from_string(value).ok_or_else(|| format!("unknown engine '{}'", value))?;
+                if arg == "--white" {
+                    options.white = engine;
+                } else {
+                    options.black = engine;
+                }
+            }
+            "--delay" => {
+                let value = value()?;
+                let ms = value.parse::<u64>().map_err(|_| format!("wrong delay '{}'", value))?;
+                options.delay = Duration::from_millis(ms);
+            }
+            "--resume" => options.resume = Some(value()?.to_string()),
+            "--rules" => options.rules = Rules::from_string(value()?)?,
+            "--time" => options.time = Some(TimeControl::from_string(value()?)?),
+            "--style" => {
+                let value = value()?;
+                options.style = Style::from_string(value).ok_or_else(|| format!("unknown style '{}'", value))?;
+            }
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok(options)
+}
+
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = commands::run(&args) {
+        std::process::exit(code);
+    }
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("error: {}\n{}", msg, commands::USAGE);
+            std::process::exit(commands::EXIT_USAGE);
+        }
+    };
+
+    let mut game = match &options.resume {
+        Some(file) => match SavedGame::load(file) {
+            Ok(saved) => Game::from_saved(saved, options.delay, options.style),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(commands::EXIT_ERROR);
+            }
+        },
+        None => Game::new(choose_players(&options), options.rules.clone()),
+    };
+    print_field_and_score(&game, false);
+    game.start_clock(options.time);
+
+    let outcome = if options.tui { play_tui(&mut game) } else { play(&mut game) };
+    let players = &game.players;
+    let code = match outcome {
+        Outcome::Over => {
+            print_field_and_score(&game, true);
+            commands::result_code(game.rules.winner(&game.field))
+        }
+        Outcome::Resigned(bw) => {
+            println!("{} RESIGNS, {} WINS", players.name(bw), players.name(OccupyType::get_opposite_type(bw)));
+            if bw == OccupyType::White { commands::EXIT_BLACK_WINS } else { commands::EXIT_WHITE_WINS }
+        }
+        Outcome::TimeOut(bw) => {
+            print_field_and_score(&game, false);
+            println!("{} LOSES ON TIME, {} WINS", players.name(bw), players.name(OccupyType::get_opposite_type(bw)));
+            if bw == OccupyType::White { commands::EXIT_BLACK_WINS } else { commands::EXIT_WHITE_WINS }
+        }
+        Outcome::Aborted => abort(),
+    };
+    std::process::exit(code);
+}
+
+
+fn choose_players(options: &Options) -> Players {
+    let mode = match options.mode {
+        Some(mode) => Some(mode),
+        None => mode_choice(),
+    };
+    let mode = mode.unwrap_or_else(|| abort());
+    let (white, black) = match mode {
+        Mode::HumanVsComputer => {
+            let player = beginning_choice().unwrap_or_else(|| abort());
+            if player == OccupyType::White {
+                (Side::Human, Side::Computer(options.black))
+            } else {
+                (Side::Computer(options.white), Side::Human)
+            }
+        }
+        Mode::HumanVsHuman => (Side::Human, Side::Human),
+        Mode::ComputerVsComputer => (Side::Computer(options.white), Side::Computer(options.black)),
+    };
+    Players { mode, white, black, delay: options.delay, style: options.style }
+}
+
+
+#[cfg(feature = "tui")]
+fn play_tui(game: &mut Game) -> Outcome {
+    match tui::play(game) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("error: terminal failure: {}", e);
+            std::process::exit(commands::EXIT_ERROR);
+        }
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn play_tui(game: &mut Game) -> Outcome {
+    play(game)
+}
+
+fn abort() -> ! {
+    println!("Game aborted");
+    std::process::exit(commands::EXIT_ABORTED);
+}
+
+fn play(game: &mut Game) -> Outcome {
+    loop {
+        let bw = game.to_move;
+        if !possible_movement(bw, &game.field) {
+            if !possible_movement(OccupyType::get_opposite_type(bw), &game.field) {
+                return Outcome::Over;
+            }
+            println!("{} PASS", game.players.name(bw));
+            game.pass();
+            continue;
+        }
+        match game.players.get(bw) {
+            Side::Human => match player_move(game) {
+                None => {}
+                Some(Action::Resign) => return Outcome::Resigned(bw),
+                Some(Action::Load(file)) => match SavedGame::load(&file) {
+                    Ok(saved) => {
+                        //the loaded game starts with full clocks
+                        let control = game.clock.as_ref().map(|c| c.control());
+                        *game = Game::from_saved(saved, game.players.delay, game.players.style);
+                        game.start_clock(control);
+                        println!(" loaded {}", file);
+                    }
+                    Err(e) => {
+                        println!(" {}", e);
+                        continue;
+                    }
+                },
+                Some(_) => return Outcome::Aborted,
+            },
+            Side::Computer(engine) => {
+                std::thread::sleep(game.players.delay);
+                engine_move(game, engine);
+            }
+        }
+        //the flag is seen after the move, the prompt does not time out
+        if let Some(bw) = game.flagged() {
+            return Outcome::TimeOut(bw);
+        }
+        print_field_and_score(game, false);
+    }
+}
@@ -0,0 +1,294 @@
+use std::io::{stdout, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+                       MouseButton, MouseEventKind};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+
+use rustversi::point::{Point, BOARD_SIZE};
+use rustversi::occupytype::OccupyType;
+use rustversi::field::FieldAction;
+use rustversi::game::possible_movement;
+
+use crate::{Game, Outcome, Side};
+
+
+const BOARD_TOP: u16 = 2;
+const BOARD_LEFT: u16 = 4;
+const CELL_WIDTH: u16 = 3;
+const PANEL_LEFT: u16 = BOARD_LEFT + CELL_WIDTH * BOARD_SIZE as u16 + 4;
+const HISTORY_LINES: usize = 12;
+const SAVE_FILE: &str = "rustversi.sav";
+//how often the running clock is redrawn
+const CLOCK_REFRESH: Duration = Duration::from_millis(100);
+
+const BOARD_COLOR: Color = Color::DarkGreen;
+const CURSOR_COLOR: Color = Color::DarkYellow;
+const LAST_MOVE_COLOR: Color = Color::DarkRed;
+const FLIP_COLOR: Color = Color::DarkCyan;
+const LEGAL_COLOR: Color = Color::Yellow;
+
+
+//restores the terminal even if the game panics
+struct Screen {
+    out: Stdout,
+}
+
+impl Screen {
+    fn new() -> std::io::Result<Screen> {
+        let mut out = stdout();
+        terminal::enable_raw_mode()?;
+        execute!(out, terminal::EnterAlternateScreen, EnableMouseCapture, cursor::Hide)?;
+        Ok(Screen { out })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, cursor::Show, DisableMouseCapture, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+
+//what the human did on the board
+enum Input {
+    Moved,
+    Resign,
+    TimeOut,
+    Quit,
+}
+
+struct Tui {
+    screen: Screen,
+    cursor: Point,
+    last: Option<Point>,
+    flips: Vec<Point>,
+    message: String,
+}
+
+
+impl Tui {
+    fn cell_position(p: &Point) -> (u16, u16) {
+        let col = BOARD_LEFT + (p.x() as u16 - 1) * CELL_WIDTH;
+        let row = BOARD_TOP + 1 + (BOARD_SIZE - p.y()) as u16;
+        (col, row)
+    }
+
+    fn point_at(col: u16, row: u16) -> Option<Point> {
+        if col < BOARD_LEFT || row < BOARD_TOP + 1 {
+            return None;
+        }
+        let x = (col - BOARD_LEFT) / CELL_WIDTH + 1;
+        let y = BOARD_SIZE as i16 - (row - BOARD_TOP - 1) as i16;
+        if !Point::check_point(x as i8, y as i8) {
+            return None;
+        }
+        Some(Point::new(x as u8, y as u8))
+    }
+
+    fn draw(&mut self, game: &Game, to_move: Option<OccupyType>) -> std::io::Result<()> {
+        let field = &game.field;
+        let players = &game.players;
+        let out = &mut self.screen.out;
+        queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0),
+               Print(format!("rustversi -- arrows/mouse: select, enter/space: move, s: save to {}, r: resign, q: quit",
+                             SAVE_FILE)))?;
+
+        for x in 1..=BOARD_SIZE {
+            let (col, _) = Tui::cell_position(&Point::new(x, 1));
+            queue!(out, cursor::MoveTo(col, BOARD_TOP), Print(format!(" {} ", x)))?;
+        }
+        let legal: Vec<Point> = match to_move {
+            Some(bw) if players.get(bw) == Side::Human =>
+                field.get_list_of_moves(bw).into_iter().map(|(p, _)| p).collect(),
+            _ => vec![],
+        };
+        for y in 1..=BOARD_SIZE {
+            let (_, row) = Tui::cell_position(&Point::new(1, y));
+            queue!(out, cursor::MoveTo(BOARD_LEFT - 3, row), Print(format!("{:>2}", y)))?;
+            for x in 1..=BOARD_SIZE {
+                let p = Point::new(x, y);
+                //the cursor is shown only when the human is to move
+                let background = if p == self.cursor && !legal.is_empty() {
+                    CURSOR_COLOR
+                } else if Some(p) == self.last {
+                    LAST_MOVE_COLOR
+                } else if self.flips.contains(&p) {
+                    FLIP_COLOR
+                } else {
+                    BOARD_COLOR
+                };
+                //the cells outside the board shape are left blank
+                let background = if field.shape().contains(&p) { background } else { Color::Reset };
+                let (symbol, foreground) = match field.get_type(&p) {
+                    _ if !field.shape().contains(&p) => ("   ", Color::Reset),
+                    OccupyType::Black => (" ● ", Color::Black),
+                    OccupyType::White => (" ● ", Color::White),
+                    OccupyType::Blocked => ("███", Color::DarkGrey),
+                    _ if legal.contains(&p) => (" · ", LEGAL_COLOR),
+                    _ => ("   ", Color::Reset),
+                };
+                let (col, row) = Tui::cell_position(&p);
+                queue!(out, cursor::MoveTo(col, row), SetBackgroundColor(background),
+                       SetForegroundColor(foreground), Print(symbol), ResetColor)?;
+            }
+            queue!(out, Print(format!(" {}", y)))?;
+        }
+
+        let (w, b) = game.rules.score(field);
+        let mut panel = vec![
+            format!("{} {}: {}", OccupyType::White, players.name(OccupyType::White), w),
+            format!("{} {}: {}", OccupyType::Black, players.name(OccupyType::Black), b),
+            String::new(),
+        ];
+        if let Some(clock) = &game.clock {
+            panel.insert(2, format!("clock {} {} : {} {}", OccupyType::White, clock.side(OccupyType::White),
+                                    OccupyType::Black, clock.side(OccupyType::Black)));
+        }
+        if let Some(bw) = to_move {
+            panel.push(format!("to move: {}", players.name(bw)));
+        }
+        panel.push(String::new());
+        panel.push("history:".to_string());
+        //white moves first, passes are in the history
+        let history = game.moves.iter().enumerate().map(|(i, m)| {
+            let bw = if i % 2 == 0 { OccupyType::White } else { OccupyType::Black };
+            match m {
+                Some(p) => format!("{:>3}. {} {}", i + 1, bw, p),
+                None => format!("{:>3}. {} pass", i + 1, bw),
+            }
+        });
+        panel.extend(history.skip(game.moves.len().saturating_sub(HISTORY_LINES)));
+        for (i, line) in panel.iter().enumerate() {
+            queue!(out, cursor::MoveTo(PANEL_LEFT, BOARD_TOP + i as u16), Print(line))?;
+        }
+        queue!(out, cursor::MoveTo(0, BOARD_TOP + BOARD_SIZE as u16 + 2), Print(&self.message))?;
+        out.flush()
+    }
+
+    fn record_move(&mut self, game: &mut Game, p: Point) {
+        self.flips = game.field.get_opposite_points(&p, game.to_move);
+        game.play_move(&p);
+        self.last = Some(p);
+        self.message.clear();
+    }
+
+    fn try_move(&mut self, game: &mut Game, p: Point) -> bool {
+        self.cursor = p;
+        let (valid, _) = game.field.is_valid_move(&p, game.to_move);
+        if !valid {
+            self.message = format!("{} -- is not valid move", p);
+            return false;
+        }
+        self.record_move(game, p);
+        true
+    }
+
+    fn human_move(&mut self, game: &mut Game) -> std::io::Result<Input> {
+        loop {
+            self.draw(game, Some(game.to_move))?;
+            //with the clock the screen is redrawn while waiting and the human may lose on time
+            if game.clock.is_some() && !event::poll(CLOCK_REFRESH)? {
+                if game.flagged().is_some() {
+                    return Ok(Input::TimeOut);
+                }
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    let (x, y) = (self.cursor.x() as i8, self.cursor.y() as i8);
+                    let (x, y) = match key.code {
+                        KeyCode::Left | KeyCode::Char('h') => (x - 1, y),
+                        KeyCode::Right | KeyCode::Char('l') => (x + 1, y),
+                        KeyCode::Up | KeyCode::Char('k') => (x, y + 1),
+                        KeyCode::Down | KeyCode::Char('j') => (x, y - 1),
+                        KeyCode::Enter | KeyCode::Char(' ') => {
+                            if self.try_move(game, self.cursor) {
+                                return Ok(Input::Moved);
+                            }
+                            continue;
+                        }
+                        KeyCode::Char('s') => {
+                            self.message = match game.to_saved().save(SAVE_FILE) {
+                                Ok(()) => format!("saved to {}", SAVE_FILE),
+                                Err(e) => e,
+                            };
+                            continue;
+                        }
+                        KeyCode::Char('r') => return Ok(Input::Resign),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(Input::Quit),
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(Input::Quit),
+                        _ => continue,
+                    };
+                    if Point::check_point(x, y) {
+                        self.cursor = Point::new(x as u8, y as u8);
+                    }
+                }
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    if let Some(p) = Tui::point_at(mouse.column, mouse.row) {
+                        if self.try_move(game, p) {
+                            return Ok(Input::Moved);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn wait_key(&mut self) -> std::io::Result<()> {
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Release {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+
+pub fn play(game: &mut Game) -> std::io::Result<Outcome> {
+    let mut tui = Tui {
+        screen: Screen::new()?,
+        cursor: Point::new(BOARD_SIZE / 2, BOARD_SIZE / 2),
+        last: None,
+        flips: vec![],
+        message: String::new(),
+    };
+
+    loop {
+        let bw = game.to_move;
+        if !possible_movement(bw, &game.field) {
+            if !possible_movement(OccupyType::get_opposite_type(bw), &game.field) {
+                break;
+            }
+            game.pass();
+            continue;
+        }
+        match game.players.get(bw) {
+            Side::Human => match tui.human_move(game)? {
+                Input::Moved => {}
+                Input::Resign => return Ok(Outcome::Resigned(bw)),
+                Input::TimeOut => return Ok(Outcome::TimeOut(bw)),
+                Input::Quit => return Ok(Outcome::Aborted),
+            },
+            Side::Computer(engine) => {
+                tui.message = format!("{} is thinking...", game.players.name(bw));
+                tui.draw(game, Some(bw))?;
+                std::thread::sleep(game.players.delay);
+                let p = engine.get_move_goal(bw, &game.field, game.rules.goal()).expect("Computer has no move");
+                tui.record_move(game, p);
+            }
+        }
+        if let Some(bw) = game.flagged() {
+            return Ok(Outcome::TimeOut(bw));
+        }
+    }
+    tui.message = "Game over, press any key".to_string();
+    tui.draw(game, None)?;
+    tui.wait_key()?;
+    Ok(Outcome::Over)
+}
@@ -0,0 +1,471 @@
+/*
+Game clocks: the remaining time of both sides under a time control, written as
+
+  5m           -- sudden death: 5 minutes for the whole game
+  5m+3s        -- increment (Fischer): 3 seconds are added after every move
+  5m/3x30s     -- byo-yomi: after the main time every move has to be made in 30 seconds, a move which
+                  takes longer uses up one of the 3 periods (or several); `0/5x10s` has no main time
+
+The times are `<n>m`, `<n>s`, `<n>ms` or the seconds as a plain number. A side whose time is over
+(no main time and no periods left) has lost on time, `Clock::flagged` tells which one.
+
+The clock reads the time from a `TimeSource`: `SystemClock` for a real game, `ManualClock` when
+the time comes from outside (the tests, the wasm API which gets `now_ms` from JS).
+
+    let mut clock = Clock::new(TimeControl::from_string("5m+3s")?, SystemClock::new());
+    clock.start(OccupyType::Black);
+    ...
+    let used = clock.press();    //black has moved, white's time is running
+*/
+use core::fmt;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::occupytype::OccupyType;
+
+
+pub trait TimeSource {
+    //milliseconds from any fixed moment
+    fn now_ms(&self) -> u64;
+}
+
+pub struct SystemClock {
+    origin: std::time::Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock { origin: std::time::Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl TimeSource for SystemClock {
+    fn now_ms(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+}
+
+//the time is set by the caller; the clones share it, so a test keeps one and gives one to the `Clock`
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<u64>>,
+}
+
+impl ManualClock {
+    pub fn new(now_ms: u64) -> Self {
+        ManualClock { now: Rc::new(Cell::new(now_ms)) }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now.set(now_ms);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.now.set(self.now.get() + ms);
+    }
+}
+
+impl TimeSource for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeControl {
+    SuddenDeath { main_ms: u64 },
+    Increment { main_ms: u64, increment_ms: u64 },
+    ByoYomi { main_ms: u64, periods: u8, period_ms: u64 },
+}
+
+fn parse_ms(s: &str) -> Option<u64> {
+    let (n, unit) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 1)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60_000)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1000)
+    } else {
+        (s, 1000)
+    };
+    n.parse::<u64>().ok().and_then(|n| n.checked_mul(unit))
+}
+
+//the largest unit of `parse_ms` which gives a whole number
+fn ms_to_string(ms: u64) -> String {
+    if ms == 0 {
+        "0".to_string()
+    } else if ms % 60_000 == 0 {
+        format!("{}m", ms / 60_000)
+    } else if ms % 1000 == 0 {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+//"m:ss", tenths of a second under 10 seconds: "0:07.4"
+pub fn format_ms(ms: u64) -> String {
+    if ms < 10_000 {
+        format!("0:{:02}.{}", ms / 1000, ms % 1000 / 100)
+    } else {
+        format!("{}:{:02}", ms / 60_000, ms % 60_000 / 1000)
+    }
+}
+
+impl TimeControl {
+    pub fn main_ms(&self) -> u64 {
+        match *self {
+            TimeControl::SuddenDeath { main_ms } => main_ms,
+            TimeControl::Increment { main_ms, .. } => main_ms,
+            TimeControl::ByoYomi { main_ms, .. } => main_ms,
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<TimeControl, String> {
+        let s = s.trim();
+        let wrong = |part: &str| format!("wrong time '{}' in the time control '{}'", part, s);
+        let time = |part: &str| parse_ms(part).ok_or_else(|| wrong(part));
+        let control = if let Some((main, increment)) = s.split_once('+') {
+            TimeControl::Increment { main_ms: time(main)?, increment_ms: time(increment)? }
+        } else if let Some((main, byo)) = s.split_once('/') {
+            let (periods, period) = byo.split_once('x').ok_or_else(|| wrong(byo))?;
+            let periods = periods.parse::<u8>().ok().filter(|n| *n > 0).ok_or_else(|| wrong(periods))?;
+            let period_ms = time(period).ok().filter(|ms| *ms > 0).ok_or_else(|| wrong(period))?;
+            TimeControl::ByoYomi { main_ms: time(main)?, periods, period_ms }
+        } else {
+            TimeControl::SuddenDeath { main_ms: time(s)? }
+        };
+        if control.main_ms() == 0 && !matches!(control, TimeControl::ByoYomi { .. }) {
+            return Err(format!("the time control '{}' gives no time", s));
+        }
+        Ok(control)
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TimeControl::SuddenDeath { main_ms } => write!(f, "{}", ms_to_string(main_ms)),
+            TimeControl::Increment { main_ms, increment_ms } =>
+                write!(f, "{}+{}", ms_to_string(main_ms), ms_to_string(increment_ms)),
+            TimeControl::ByoYomi { main_ms, periods, period_ms } =>
+                write!(f, "{}/{}x{}", ms_to_string(main_ms), periods, ms_to_string(period_ms)),
+        }
+    }
+}
+
+
+//the time of one side
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SideClock {
+    pub main_ms: u64,
+    //byo-yomi periods left, 0 for the other controls
+    pub periods: u8,
+    //what is left of the current byo-yomi period
+    pub period_ms: u64,
+}
+
+impl SideClock {
+    fn new(control: TimeControl) -> Self {
+        match control {
+            TimeControl::ByoYomi { main_ms, periods, period_ms } => SideClock { main_ms, periods, period_ms },
+            _ => SideClock { main_ms: control.main_ms(), periods: 0, period_ms: 0 },
+        }
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.main_ms == 0 && self.periods == 0
+    }
+
+    //the clock after `used` ms of thinking, period_ms is the length of a byo-yomi period
+    fn spend(mut self, used: u64, period_ms: u64) -> Self {
+        let from_main = used.min(self.main_ms);
+        self.main_ms -= from_main;
+        let mut used = used - from_main;
+        if self.main_ms > 0 || self.periods == 0 {
+            return self;
+        }
+        //byo-yomi: every period which runs out is lost
+        while used > 0 && self.periods > 0 {
+            let from_period = used.min(self.period_ms);
+            self.period_ms -= from_period;
+            used -= from_period;
+            if self.period_ms == 0 {
+                self.periods -= 1;
+                self.period_ms = if self.periods > 0 { period_ms } else { 0 };
+            }
+        }
+        self
+    }
+}
+
+impl fmt::Display for SideClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.main_ms > 0 || self.periods == 0 {
+            write!(f, "{}", format_ms(self.main_ms))?;
+        } else {
+            write!(f, "{}", format_ms(self.period_ms))?;
+        }
+        if self.periods > 0 {
+            write!(f, " ({}x)", self.periods)?;
+        }
+        Ok(())
+    }
+}
+
+
+pub struct Clock<T: TimeSource> {
+    control: TimeControl,
+    source: T,
+    white: SideClock,
+    black: SideClock,
+    //the side whose time is running and since when
+    running: Option<(OccupyType, u64)>,
+    //the thinking time of every move (and pass) in the order of the game
+    moves: Vec<(OccupyType, u64)>,
+}
+
+impl<T: TimeSource> Clock<T> {
+    pub fn new(control: TimeControl, source: T) -> Self {
+        Clock { control, source, white: SideClock::new(control), black: SideClock::new(control), running: None, moves: vec![] }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    //the side whose time is running
+    pub fn to_move(&self) -> Option<OccupyType> {
+        self.running.map(|(bw, _)| bw)
+    }
+
+    fn period_ms(&self) -> u64 {
+        match self.control {
+            TimeControl::ByoYomi { period_ms, .. } => period_ms,
+            _ => 0,
+        }
+    }
+
+    fn stored(&self, bw: OccupyType) -> SideClock {
+        if bw == OccupyType::White { self.white } else { self.black }
+    }
+
+    //the time of the side now, the running time included
+    pub fn side(&self, bw: OccupyType) -> SideClock {
+        match self.running {
+            Some((running, since)) if running == bw =>
+                self.stored(bw).spend(self.source.now_ms().saturating_sub(since), self.period_ms()),
+            _ => self.stored(bw),
+        }
+    }
+
+    //the side which has lost on time
+    pub fn flagged(&self) -> Option<OccupyType> {
+        [OccupyType::Black, OccupyType::White].iter().copied().find(|bw| self.side(*bw).is_flagged())
+    }
+
+    //the time bw may think before it loses on time: the main time and all of the byo-yomi periods
+    pub fn until_flagged(&self, bw: OccupyType) -> u64 {
+        let side = self.side(bw);
+        match side.periods {
+            0 => side.main_ms,
+            n => side.main_ms + side.period_ms + (n as u64 - 1) * self.period_ms(),
+        }
+    }
+
+    //the time of bw starts running (the game starts or goes on after `stop`)
+    pub fn start(&mut self, bw: OccupyType) {
+        self.running = Some((bw, self.source.now_ms()));
+    }
+
+    //the running side has finished its turn: the increment is added, a byo-yomi period starts again;
+    //the time used, 0 if no time is running
+    pub fn stop(&mut self) -> u64 {
+        let (bw, since) = match self.running.take() {
+            Some(running) => running,
+            None => return 0,
+        };
+        let used = self.source.now_ms().saturating_sub(since);
+        let mut side = self.stored(bw).spend(used, self.period_ms());
+        if !side.is_flagged() {
+            match self.control {
+                TimeControl::Increment { increment_ms, .. } => side.main_ms += increment_ms,
+                TimeControl::ByoYomi { period_ms, .. } if side.main_ms == 0 => side.period_ms = period_ms,
+                _ => {}
+            }
+        }
+        if bw == OccupyType::White {
+            self.white = side;
+        } else {
+            self.black = side;
+        }
+        self.moves.push((bw, used));
+        used
+    }
+
+    //a move (or a pass) is made: `stop` and the time of the other side starts running
+    pub fn press(&mut self) -> u64 {
+        let bw = match self.to_move() {
+            Some(bw) => bw,
+            None => return 0,
+        };
+        let used = self.stop();
+        self.start(OccupyType::get_opposite_type(bw));
+        used
+    }
+
+    //the time of every move of the game
+    pub fn move_times(&self) -> &[(OccupyType, u64)] {
+        &self.moves
+    }
+
+    pub fn last_move_ms(&self) -> Option<u64> {
+        self.moves.last().map(|(_, ms)| *ms)
+    }
+
+    //"<control> <running side or -> <since> <white main> <periods> <period> <black main> <periods> <period>",
+    //a state which does not depend on the time source: the wasm API keeps it between the calls
+    pub fn state(&self) -> String {
+        let (running, since) = match self.running {
+            Some((bw, since)) => (bw.to_string(), since),
+            None => ("-".to_string(), 0),
+        };
+        let side = |c: SideClock| format!("{} {} {}", c.main_ms, c.periods, c.period_ms);
+        format!("{} {} {} {} {}", self.control, running, since, side(self.white), side(self.black))
+    }
+
+    pub fn from_state(s: &str, source: T) -> Result<Clock<T>, String> {
+        let wrong = || format!("wrong clock state '{}'", s);
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 9 {
+            return Err(wrong());
+        }
+        let control = TimeControl::from_string(parts[0])?;
+        let number = |i: usize| parts[i].parse::<u64>().map_err(|_| wrong());
+        let side = |i: usize| -> Result<SideClock, String> {
+            let periods = parts[i + 1].parse::<u8>().map_err(|_| wrong())?;
+            Ok(SideClock { main_ms: number(i)?, periods, period_ms: number(i + 2)? })
+        };
+        let running = match parts[1] {
+            "-" => None,
+            "#" => Some((OccupyType::Black, number(2)?)),
+            "O" => Some((OccupyType::White, number(2)?)),
+            _ => return Err(wrong()),
+        };
+        Ok(Clock { control, source, white: side(3)?, black: side(6)?, running, moves: vec![] })
+    }
+}
+
+
+#[test]
+fn clock_control_names_test() {
+    for s in ["5m", "90s", "5m+3s", "1m+500ms", "5m/3x30s", "0/5x10s"] {
+        assert_eq!(s, TimeControl::from_string(s).unwrap().to_string());
+    }
+    assert_eq!(TimeControl::SuddenDeath { main_ms: 300_000 }, TimeControl::from_string("300").unwrap());
+    assert_eq!(TimeControl::ByoYomi { main_ms: 60_000, periods: 2, period_ms: 10_000 },
+               TimeControl::from_string("1m/2x10").unwrap());
+    for s in ["", "0", "5h", "5m+", "5m/30s", "5m/0x30s", "5m/3x0"] {
+        assert!(TimeControl::from_string(s).is_err(), "{}", s);
+    }
+    assert_eq!("4:32", format_ms(272_400));
+    assert_eq!("0:07.4", format_ms(7_450));
+}
+
+#[test]
+fn clock_sudden_death_test() {
+    let time = ManualClock::new(1000);
+    let mut clock = Clock::new(TimeControl::from_string("1m").unwrap(), time.clone());
+    clock.start(OccupyType::Black);
+    time.advance(20_000);
+    assert_eq!(40_000, clock.side(OccupyType::Black).main_ms);
+    assert_eq!(20_000, clock.press());
+    assert_eq!(Some(OccupyType::White), clock.to_move());
+    time.advance(5_000);
+    clock.press();
+    assert_eq!(vec![(OccupyType::Black, 20_000), (OccupyType::White, 5_000)], clock.move_times());
+    assert_eq!(None, clock.flagged());
+    //black runs out while thinking
+    time.advance(40_000);
+    assert_eq!(Some(OccupyType::Black), clock.flagged());
+    assert_eq!("0:00.0", clock.side(OccupyType::Black).to_string());
+    assert_eq!(55_000, clock.side(OccupyType::White).main_ms);
+}
+
+#[test]
+fn clock_increment_test() {
+    let time = ManualClock::new(0);
+    let mut clock = Clock::new(TimeControl::from_string("10s+2s").unwrap(), time.clone());
+    clock.start(OccupyType::White);
+    time.advance(1_500);
+    clock.press();
+    assert_eq!(10_500, clock.side(OccupyType::White).main_ms);
+    //no increment after the flag
+    time.advance(12_000);
+    clock.stop();
+    assert!(clock.side(OccupyType::Black).is_flagged());
+    assert_eq!(None, clock.to_move());
+    assert_eq!(0, clock.stop());
+}
+
+#[test]
+fn clock_byo_yomi_test() {
+    let time = ManualClock::new(0);
+    let mut clock = Clock::new(TimeControl::from_string("10s/3x5s").unwrap(), time.clone());
+    clock.start(OccupyType::Black);
+    //the main time and 4 seconds of the first period
+    time.advance(14_000);
+    assert_eq!(SideClock { main_ms: 0, periods: 3, period_ms: 1_000 }, clock.side(OccupyType::Black));
+    clock.press();
+    //the period starts again after the move
+    assert_eq!(SideClock { main_ms: 0, periods: 3, period_ms: 5_000 }, clock.side(OccupyType::Black));
+    time.advance(1_000);
+    clock.press();
+    //two periods run out
+    time.advance(11_000);
+    assert_eq!("0:04.0 (1x)", clock.side(OccupyType::Black).to_string());
+    clock.press();
+    time.advance(1_000);
+    clock.press();
+    time.advance(5_000);
+    assert_eq!(Some(OccupyType::Black), clock.flagged());
+}
+
+#[test]
+fn clock_until_flagged_test() {
+    let time = ManualClock::new(0);
+    let mut clock = Clock::new(TimeControl::from_string("10s/3x5s").unwrap(), time.clone());
+    assert_eq!(25_000, clock.until_flagged(OccupyType::Black));
+    clock.start(OccupyType::Black);
+    time.advance(12_000);
+    assert_eq!(13_000, clock.until_flagged(OccupyType::Black));
+    time.advance(13_000);
+    assert_eq!(0, clock.until_flagged(OccupyType::Black));
+    let clock = Clock::new(TimeControl::from_string("5m+3s").unwrap(), ManualClock::new(0));
+    assert_eq!(300_000, clock.until_flagged(OccupyType::White));
+}
+
+#[test]
+fn clock_state_test() {
+    let time = ManualClock::new(0);
+    let mut clock = Clock::new(TimeControl::from_string("5m/3x30s").unwrap(), time.clone());
+    clock.start(OccupyType::Black);
+    time.set(61_000);
+    clock.press();
+    let state = clock.state();
+    assert_eq!("5m/3x30s O 61000 300000 3 30000 239000 3 30000", state);
+    time.advance(1_000);
+    let restored = Clock::from_state(&state, time.clone()).unwrap();
+    assert_eq!(299_000, restored.side(OccupyType::White).main_ms);
+    assert_eq!(state, restored.state());
+    assert!(Clock::from_state("5m O 1 2", time.clone()).is_err());
+    assert!(Clock::from_state("5m X 0 1 0 0 1 0 0", time).is_err());
+}
@@ -0,0 +1,471 @@
+/*
+Board diagrams for documents and bug reports: SVG, and PNG with the `png` feature.
+The coordinates are the ones of `Field::to_string`: x 1..8 from the left, y 8..1 from the top.
+
+    let mut d = Diagram::new();
+    d.last_move = Some(Point::new(5, 6));
+    d.legal_moves = Some(OccupyType::Black);
+    d.arrows.push((Point::new(4, 6), Point::new(4, 4)));
+    let svg = d.to_svg(&field);
+*/
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+
+
+type Color = [u8; 3];
+
+const BACKGROUND: Color = [255, 255, 255];
+const BOARD: Color = [46, 125, 50];
+const GRID: Color = [27, 61, 29];
+//blocked cells are filled with it
+const HOLE: Color = [90, 90, 90];
+const LABEL: Color = [51, 51, 51];
+const BLACK: Color = [17, 17, 17];
+const WHITE: Color = [245, 245, 245];
+const LAST_MOVE: Color = [211, 47, 47];
+const ARROW: Color = [21, 101, 192];
+const LEGAL_OPACITY: f32 = 0.4;
+const ARROW_OPACITY: f32 = 0.8;
+
+pub const DEFAULT_CELL_SIZE: u32 = 40;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    //pixels, the discs and the labels are scaled with it
+    pub cell_size: u32,
+    pub coordinates: bool,
+    pub last_move: Option<Point>,
+    //marks the legal moves of the side
+    pub legal_moves: Option<OccupyType>,
+    //numbers drawn on the discs, see `number_moves`
+    pub numbers: Vec<(Point, u32)>,
+    pub arrows: Vec<(Point, Point)>,
+}
+
+
+fn hex(c: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+fn disc_color(bw: OccupyType) -> Color {
+    if bw == OccupyType::White { WHITE } else { BLACK }
+}
+
+fn text_color(bw: OccupyType) -> Color {
+    if bw == OccupyType::White { BLACK } else { WHITE }
+}
+
+//"x,y:x,y"
+pub fn parse_arrow(s: &str) -> Option<(Point, Point)> {
+    let (from, to) = s.split_once(':')?;
+    Some((Point::from_string(from)?, Point::from_string(to)?))
+}
+
+
+impl Default for Diagram {
+    fn default() -> Self {
+        Diagram {
+            cell_size: DEFAULT_CELL_SIZE,
+            coordinates: true,
+            last_move: None,
+            legal_moves: None,
+            numbers: vec![],
+            arrows: vec![],
+        }
+    }
+}
+
+impl Diagram {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    //numbers the moves 1, 2, ... (passes are counted), a later move on the same square wins
+    pub fn number_moves(&mut self, moves: &[Option<Point>]) {
+        for (i, m) in moves.iter().enumerate() {
+            if let Some(p) = m {
+                self.numbers.retain(|(q, _)| q != p);
+                self.numbers.push((*p, i as u32 + 1));
+            }
+        }
+    }
+
+    fn margin(&self) -> u32 {
+        if self.coordinates { self.cell_size * 3 / 5 } else { self.cell_size / 10 }
+    }
+
+    //(width, height) in pixels
+    pub fn size(&self) -> (u32, u32) {
+        let side = self.cell_size * BOARD_SIZE as u32 + 2 * self.margin();
+        (side, side)
+    }
+
+    //top left corner of the cell
+    fn cell_origin(&self, p: &Point) -> (u32, u32) {
+        let m = self.margin();
+        (m + (p.x() as u32 - 1) * self.cell_size, m + (BOARD_SIZE - p.y()) as u32 * self.cell_size)
+    }
+
+    fn cell_center(&self, p: &Point) -> (f32, f32) {
+        let (x, y) = self.cell_origin(p);
+        let half = self.cell_size as f32 / 2.0;
+        (x as f32 + half, y as f32 + half)
+    }
+
+    fn legal(&self, field: &Field) -> Vec<Point> {
+        match self.legal_moves {
+            Some(bw) => field.get_list_of_moves(bw).into_iter().map(|(p, _)| p).collect(),
+            None => vec![],
+        }
+    }
+
+    //the arrow line ends at the base of the head
+    fn arrow_geometry(&self, from: &Point, to: &Point) -> ((f32, f32), (f32, f32), (f32, f32)) {
+        let (x1, y1) = self.cell_center(from);
+        let (x2, y2) = self.cell_center(to);
+        let head = self.cell_size as f32 / 3.0;
+        let len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+        let (dx, dy) = ((x2 - x1) / len, (y2 - y1) / len);
+        ((x1, y1), (x2 - dx * head, y2 - dy * head), (x2, y2))
+    }
+
+    pub fn to_svg(&self, field: &Field) -> String {
+        let (width, height) = self.size();
+        let c = self.cell_size as f32;
+        let m = self.margin();
+        let board = self.cell_size * BOARD_SIZE as u32;
+        let mut ret = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+                              width, height, width, height);
+        if !self.arrows.is_empty() {
+            ret.push_str(&format!("<defs><marker id=\"arrowhead\" markerWidth=\"4\" markerHeight=\"4\" refX=\"0\" refY=\"2\" \
+                                   orient=\"auto\" markerUnits=\"strokeWidth\"><path d=\"M0,0 L4,2 L0,4 z\" fill=\"{}\"/></marker></defs>\n",
+                                  hex(ARROW)));
+        }
+        ret.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, height, hex(BACKGROUND)));
+        ret.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+                              m, m, board, board, hex(BOARD), hex(GRID)));
+        for i in 1..BOARD_SIZE as u32 {
+            let at = m + i * self.cell_size;
+            ret.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\n", at, m, at, m + board, hex(GRID)));
+            ret.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\n", m, at, m + board, at, hex(GRID)));
+        }
+
+        if self.coordinates {
+            let font = c * 0.35;
+            for i in 1..=BOARD_SIZE {
+                let (x, _) = self.cell_center(&Point::new(i, BOARD_SIZE));
+                let (_, y) = self.cell_center(&Point::new(1, i));
+                let label = m as f32 / 2.0;
+                ret.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\" text-anchor=\"middle\" \
+                                       dominant-baseline=\"central\" fill=\"{}\">{}</text>\n", x, label, font, hex(LABEL), i));
+                ret.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\" text-anchor=\"middle\" \
+                                       dominant-baseline=\"central\" fill=\"{}\">{}</text>\n", label, y, font, hex(LABEL), i));
+            }
+        }
+
+        if let Some(p) = self.last_move {
+            let (x, y) = self.cell_origin(&p);
+            ret.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                                  x + 2, y + 2, self.cell_size.saturating_sub(4), self.cell_size.saturating_sub(4), hex(LAST_MOVE), (c / 13.0).max(2.0)));
+        }
+        for x in 1..=BOARD_SIZE {
+            for y in 1..=BOARD_SIZE {
+                let p = Point::new(x, y);
+                let bw = field.get_type(&p);
+                if bw == OccupyType::Empty {
+                    continue;
+                }
+                //the cells outside the board shape are cut out
+                if !field.shape().contains(&p) {
+                    let (x, y) = self.cell_origin(&p);
+                    ret.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                                          x, y, self.cell_size, self.cell_size, hex(BACKGROUND)));
+                    continue;
+                }
+                if bw == OccupyType::Blocked {
+                    let (x, y) = self.cell_origin(&p);
+                    ret.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                                          x + 1, y + 1, self.cell_size.saturating_sub(2), self.cell_size.saturating_sub(2), hex(HOLE)));
+                    continue;
+                }
+                let (cx, cy) = self.cell_center(&p);
+                ret.push_str(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>\n",
+                                      cx, cy, c * 0.42, hex(disc_color(bw)), hex(BLACK)));
+            }
+        }
+        if let Some(bw) = self.legal_moves {
+            for p in self.legal(field) {
+                let (cx, cy) = self.cell_center(&p);
+                ret.push_str(&format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>\n",
+                                      cx, cy, c * 0.12, hex(disc_color(bw)), LEGAL_OPACITY));
+            }
+        }
+        for (p, n) in &self.numbers {
+            let (cx, cy) = self.cell_center(p);
+            let color = match field.get_type(p) {
+                OccupyType::Empty => LABEL,
+                bw => text_color(bw),
+            };
+            ret.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\" text-anchor=\"middle\" \
+                                   dominant-baseline=\"central\" fill=\"{}\">{}</text>\n", cx, cy, c * 0.4, hex(color), n));
+        }
+        for (from, to) in &self.arrows {
+            let ((x1, y1), (x2, y2), _) = self.arrow_geometry(from, to);
+            ret.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-opacity=\"{}\" stroke-width=\"{}\" \
+                                   marker-end=\"url(#arrowhead)\"/>\n",
+                                  x1, y1, x2, y2, hex(ARROW), ARROW_OPACITY, c / 12.0));
+        }
+        ret.push_str("</svg>\n");
+        ret
+    }
+
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, field: &Field) -> Vec<u8> {
+        let (width, height) = self.size();
+        let c = self.cell_size as f32;
+        let m = self.margin() as f32;
+        let board = c * BOARD_SIZE as f32;
+        let mut canvas = Canvas::new(width, height, BACKGROUND);
+        canvas.fill_rect(m, m, m + board, m + board, BOARD);
+        for i in 0..=BOARD_SIZE as u32 {
+            let at = m + i as f32 * c;
+            canvas.fill_rect(at - 1.0, m - 1.0, at + 1.0, m + board + 1.0, GRID);
+            canvas.fill_rect(m - 1.0, at - 1.0, m + board + 1.0, at + 1.0, GRID);
+        }
+
+        let scale = (self.cell_size / 16).max(1);
+        if self.coordinates {
+            for i in 1..=BOARD_SIZE {
+                let (x, _) = self.cell_center(&Point::new(i, BOARD_SIZE));
+                let (_, y) = self.cell_center(&Point::new(1, i));
+                canvas.text(x, m / 2.0, &i.to_string(), scale, LABEL);
+                canvas.text(m / 2.0, y, &i.to_string(), scale, LABEL);
+            }
+        }
+
+        if let Some(p) = self.last_move {
+            let (x, y) = self.cell_origin(&p);
+            let (x, y) = (x as f32 + 2.0, y as f32 + 2.0);
+            let w = (c / 13.0).max(2.0);
+            let e = c - 4.0;
+            canvas.fill_rect(x, y, x + e, y + w, LAST_MOVE);
+            canvas.fill_rect(x, y + e - w, x + e, y + e, LAST_MOVE);
+            canvas.fill_rect(x, y, x + w, y + e, LAST_MOVE);
+            canvas.fill_rect(x + e - w, y, x + e, y + e, LAST_MOVE);
+        }
+        for x in 1..=BOARD_SIZE {
+            for y in 1..=BOARD_SIZE {
+                let p = Point::new(x, y);
+                let bw = field.get_type(&p);
+                if bw == OccupyType::Empty {
+                    continue;
+                }
+                if !field.shape().contains(&p) {
+                    let (x, y) = self.cell_origin(&p);
+                    let (x, y) = (x as f32, y as f32);
+                    canvas.fill_rect(x, y, x + c, y + c, BACKGROUND);
+                    continue;
+                }
+                if bw == OccupyType::Blocked {
+                    let (x, y) = self.cell_origin(&p);
+                    let (x, y) = (x as f32 + 1.0, y as f32 + 1.0);
+                    canvas.fill_rect(x, y, x + c - 2.0, y + c - 2.0, HOLE);
+                    continue;
+                }
+                let (cx, cy) = self.cell_center(&p);
+                canvas.fill_circle(cx, cy, c * 0.42 + 1.0, BLACK, 1.0);
+                canvas.fill_circle(cx, cy, c * 0.42, disc_color(bw), 1.0);
+            }
+        }
+        if let Some(bw) = self.legal_moves {
+            for p in self.legal(field) {
+                let (cx, cy) = self.cell_center(&p);
+                canvas.fill_circle(cx, cy, c * 0.12, disc_color(bw), LEGAL_OPACITY);
+            }
+        }
+        for (p, n) in &self.numbers {
+            let (cx, cy) = self.cell_center(p);
+            let color = match field.get_type(p) {
+                OccupyType::Empty => LABEL,
+                bw => text_color(bw),
+            };
+            canvas.text(cx, cy, &n.to_string(), scale, color);
+        }
+        for (from, to) in &self.arrows {
+            let (start, base, end) = self.arrow_geometry(from, to);
+            canvas.line(start, base, c / 12.0, ARROW, ARROW_OPACITY);
+            canvas.arrow_head(base, end, c / 6.0, ARROW, ARROW_OPACITY);
+        }
+        canvas.encode()
+    }
+}
+
+
+//3x5 digits for the PNG labels, one row per byte, the bits are the columns
+#[cfg(feature = "png")]
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+//RGB pixels
+#[cfg(feature = "png")]
+struct Canvas {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "png")]
+impl Canvas {
+    fn new(width: u32, height: u32, background: Color) -> Self {
+        let data = background.iter().cloned().cycle().take((width * height * 3) as usize).collect();
+        Canvas { width, height, data }
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: Color, alpha: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 || alpha <= 0.0 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 3) as usize;
+        for (pixel, c) in self.data[i..i + 3].iter_mut().zip(color.iter()) {
+            let old = *pixel as f32;
+            *pixel = (old + (*c as f32 - old) * alpha.min(1.0)).round() as u8;
+        }
+    }
+
+    //calls `coverage` for the pixel centers of the box
+    fn paint<F: Fn(f32, f32) -> f32>(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color, coverage: F) {
+        for y in y1.floor() as i64..=y2.ceil() as i64 {
+            for x in x1.floor() as i64..=x2.ceil() as i64 {
+                let alpha = coverage(x as f32 + 0.5, y as f32 + 0.5);
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn fill_rect(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: Color) {
+        self.paint(x1, y1, x2 - 1.0, y2 - 1.0, color, |x, y| {
+            if x >= x1 && x < x2 && y >= y1 && y < y2 { 1.0 } else { 0.0 }
+        });
+    }
+
+    fn fill_circle(&mut self, cx: f32, cy: f32, r: f32, color: Color, opacity: f32) {
+        self.paint(cx - r, cy - r, cx + r, cy + r, color, |x, y| {
+            let d = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+            (r - d + 0.5).clamp(0.0, 1.0) * opacity
+        });
+    }
+
+    fn line(&mut self, (x1, y1): (f32, f32), (x2, y2): (f32, f32), width: f32, color: Color, opacity: f32) {
+        let len2 = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).max(1.0);
+        let w = width / 2.0;
+        self.paint(x1.min(x2) - w, y1.min(y2) - w, x1.max(x2) + w, y1.max(y2) + w, color, |x, y| {
+            let t = (((x - x1) * (x2 - x1) + (y - y1) * (y2 - y1)) / len2).clamp(0.0, 1.0);
+            let d = ((x - x1 - t * (x2 - x1)).powi(2) + (y - y1 - t * (y2 - y1)).powi(2)).sqrt();
+            (w - d + 0.5).clamp(0.0, 1.0) * opacity
+        });
+    }
+
+    //a triangle from the base center to the tip
+    fn arrow_head(&mut self, (bx, by): (f32, f32), (tx, ty): (f32, f32), half_width: f32, color: Color, opacity: f32) {
+        let len = ((tx - bx).powi(2) + (ty - by).powi(2)).sqrt().max(1.0);
+        let (nx, ny) = (-(ty - by) / len * half_width, (tx - bx) / len * half_width);
+        let corners = [(bx + nx, by + ny), (tx, ty), (bx - nx, by - ny)];
+        let xs = corners.iter().map(|c| c.0);
+        let ys = corners.iter().map(|c| c.1);
+        let (x1, x2) = (xs.clone().fold(f32::MAX, f32::min), xs.fold(f32::MIN, f32::max));
+        let (y1, y2) = (ys.clone().fold(f32::MAX, f32::min), ys.fold(f32::MIN, f32::max));
+        let side = |(ax, ay): (f32, f32), (bx, by): (f32, f32), x: f32, y: f32| (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+        self.paint(x1, y1, x2, y2, color, |x, y| {
+            let s = [side(corners[0], corners[1], x, y), side(corners[1], corners[2], x, y), side(corners[2], corners[0], x, y)];
+            if s.iter().all(|v| *v >= 0.0) || s.iter().all(|v| *v <= 0.0) { opacity } else { 0.0 }
+        });
+    }
+
+    //digits centered on the point
+    fn text(&mut self, cx: f32, cy: f32, s: &str, scale: u32, color: Color) {
+        let digits: Vec<usize> = s.chars().filter_map(|c| c.to_digit(10)).map(|d| d as usize).collect();
+        let scale = scale as f32;
+        let width = (digits.len() as f32 * 4.0 - 1.0) * scale;
+        let left = (cx - width / 2.0).round();
+        let top = (cy - 2.5 * scale).round();
+        for (i, d) in digits.iter().enumerate() {
+            for (row, bits) in DIGITS[*d].iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        let x = left + (i as f32 * 4.0 + col as f32) * scale;
+                        let y = top + row as f32 * scale;
+                        self.fill_rect(x, y, x + scale, y + scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut ret = vec![];
+        {
+            let mut encoder = png::Encoder::new(&mut ret, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            //writing into memory does not fail
+            let mut writer = encoder.write_header().expect("Cannot write the PNG header");
+            writer.write_image_data(&self.data).expect("Cannot write the PNG data");
+        }
+        ret
+    }
+}
+
+
+#[test]
+fn diagram_svg_test() {
+    let mut f = Field::new();
+    f.init();
+    let mut d = Diagram::new();
+    let svg = d.to_svg(&f);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"368\" height=\"368\""));
+    assert_eq!(4, svg.matches("<circle").count());
+    assert_eq!(16, svg.matches("<text").count());
+
+    f.move_in_game(&Point::new(5, 6), OccupyType::White);
+    d.coordinates = false;
+    d.last_move = Some(Point::new(5, 6));
+    d.legal_moves = Some(OccupyType::Black);
+    d.number_moves(&[Some(Point::new(5, 6))]);
+    d.arrows.push(parse_arrow("4,6:4,4").unwrap());
+    let svg = d.to_svg(&f);
+    let legal = f.get_list_of_moves(OccupyType::Black).len();
+    assert_eq!(5 + legal, svg.matches("<circle").count());
+    assert_eq!(1, svg.matches("<text").count());
+    assert!(svg.contains(">1</text>"));
+    assert!(svg.contains("marker-end=\"url(#arrowhead)\""));
+    assert!(svg.contains(&format!("stroke=\"{}\"", hex(LAST_MOVE))));
+    assert_eq!(None, parse_arrow("4,6"));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn diagram_png_test() {
+    let mut f = Field::new();
+    f.init();
+    let mut d = Diagram::new();
+    d.cell_size = 20;
+    d.arrows.push((Point::new(1, 1), Point::new(8, 8)));
+    let data = d.to_png(&f);
+    assert_eq!(b"\x89PNG\r\n\x1a\n", &data[..8]);
+    let decoder = png::Decoder::new(&data[..]);
+    let reader = decoder.read_info().unwrap();
+    let (width, height) = d.size();
+    assert_eq!((width, height), (reader.info().width, reader.info().height));
+}
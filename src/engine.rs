@@ -0,0 +1,319 @@
+use core::fmt;
+use rand::prelude::*;
+
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::FieldAction;
+#[cfg(test)]
+use crate::field::Field;
+use crate::game::{computer_get_fewest_moves, computer_get_move};
+
+
+//classic positional weights, symmetric so the orientation does not matter
+const WEIGHTS: [[i32; 8]; 8] = [
+    [100, -20, 10, 5, 5, 10, -20, 100],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [10, -2, -1, -1, -1, -1, -2, 10],
+    [5, -2, -1, -1, -1, -1, -2, 5],
+    [5, -2, -1, -1, -1, -1, -2, 5],
+    [10, -2, -1, -1, -1, -1, -2, 10],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [100, -20, 10, 5, 5, 10, -20, 100],
+];
+const WIN_SCORE: i32 = 10000;
+pub const MAX_SEARCH_DEPTH: u8 = 8;
+
+
+//the computer players, from the weakest to the strongest
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Engine {
+    //any legal move
+    Random,
+    //the move which flips most discs, corners are not preferred
+    Greedy,
+    //greedy with corners first and no corners given away (the original computer player)
+    #[default]
+    Corner,
+    //alpha-beta search to the given depth
+    Search(u8),
+}
+
+//what the computer player plays for
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Goal {
+    #[default]
+    MostDiscs,
+    //misère (anti-reversi), the fewest discs win
+    FewestDiscs,
+}
+
+
+impl Engine {
+    //"random", "greedy", "corner", "search" or "search:<depth>"
+    pub fn from_string(s: &str) -> Option<Engine> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "random" => return Some(Engine::Random),
+            "greedy" => return Some(Engine::Greedy),
+            "corner" => return Some(Engine::Corner),
+            "search" => return Some(Engine::Search(4)),
+            _ => {}
+        }
+        let depth = s.strip_prefix("search:")?.parse::<u8>().ok()?;
+        if depth == 0 || depth > MAX_SEARCH_DEPTH {
+            return None;
+        }
+        Some(Engine::Search(depth))
+    }
+
+    //None if there is no move (pass)
+    pub fn get_move<T>(&self, bw: OccupyType, f: &T) -> Option<Point> where T: FieldAction + Clone {
+        self.get_move_goal(bw, f, Goal::MostDiscs)
+    }
+
+    //with `Goal::FewestDiscs` greedy and corner flip the fewest discs (corner stays off the corners),
+    //search plays for the misère result
+    pub fn get_move_goal<T>(&self, bw: OccupyType, f: &T, goal: Goal) -> Option<Point> where T: FieldAction + Clone {
+        let moves = f.get_list_of_moves(bw);
+        if moves.is_empty() {
+            return None;
+        }
+        let mut rng = rand::thread_rng();
+        match self {
+            Engine::Random => Some(moves[rng.gen_range(0..moves.len())].0),
+            Engine::Greedy | Engine::Corner if goal == Goal::FewestDiscs => {
+                let best = computer_get_fewest_moves(*self == Engine::Corner, &moves, f);
+                Some(best[rng.gen_range(0..best.len())])
+            }
+            Engine::Greedy => {
+                let max = moves.iter().map(|(_, n)| *n).max();
+                let best: Vec<Point> = moves.iter().filter(|(_, n)| Some(*n) == max).map(|(p, _)| *p).collect();
+                Some(best[rng.gen_range(0..best.len())])
+            }
+            Engine::Corner => computer_get_move(bw, f),
+            Engine::Search(depth) => {
+                let mut best: Option<Point> = None;
+                let mut alpha = -WIN_SCORE - 64;
+                for (p, _) in moves {
+                    let mut next = f.clone();
+                    next.move_in_game(&p, bw);
+                    let score = -negamax(&next, OccupyType::get_opposite_type(bw), depth - 1, -WIN_SCORE - 64, -alpha, goal);
+                    if best.is_none() || score > alpha {
+                        alpha = score;
+                        best = Some(p);
+                    }
+                }
+                best
+            }
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Engine::Random => write!(f, "random"),
+            Engine::Greedy => write!(f, "greedy"),
+            Engine::Corner => write!(f, "corner"),
+            Engine::Search(depth) => write!(f, "search:{}", depth),
+        }
+    }
+}
+
+//serialized as the `from_string` name, e.g. "search:4"
+#[cfg(feature = "serde")]
+impl serde::Serialize for Engine {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Engine {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        Engine::from_string(&s).ok_or_else(|| serde::de::Error::custom(format!("unknown engine '{}'", s)))
+    }
+}
+
+
+//static evaluation from the point of view of bw
+pub fn evaluate<T>(bw: OccupyType, f: &T) -> i32 where T: FieldAction {
+    evaluate_goal(bw, f, Goal::MostDiscs)
+}
+
+//for misère the stable squares are bad, so the positional weights change the sign; mobility is good anyway
+pub fn evaluate_goal<T>(bw: OccupyType, f: &T, goal: Goal) -> i32 where T: FieldAction {
+    let (white, black) = f.get_position_wb();
+    let weight = |points: &Vec<Point>| -> i32 {
+        //the corners of a shaped board are where the board ends
+        points.iter().map(|p| if f.is_corner(p) { WEIGHTS[0][0] } else { WEIGHTS[(p.y() - 1) as usize][(p.x() - 1) as usize] }).sum()
+    };
+    let mobility = f.get_list_of_moves(bw).len() as i32
+        - f.get_list_of_moves(OccupyType::get_opposite_type(bw)).len() as i32;
+    let (own, other) = if bw == OccupyType::White { (&white, &black) } else { (&black, &white) };
+    let position = weight(own) - weight(other);
+    let position = if goal == Goal::FewestDiscs { -position } else { position };
+    position + 5 * mobility
+}
+
+//the exact score of every legal move of bw by a search to the depth, the best first; a depth reaching
+//the end of the game gives the final results: above 0 is a win, 0 a draw
+pub fn score_moves<T>(bw: OccupyType, f: &T, depth: u8, goal: Goal) -> Vec<(Point, i32)> where T: FieldAction + Clone {
+    let mut scores: Vec<(Point, i32)> = f.get_list_of_moves(bw).into_iter().map(|(p, _)| {
+        let mut next = f.clone();
+        next.move_in_game(&p, bw);
+        let depth = depth.saturating_sub(1);
+        (p, -negamax(&next, OccupyType::get_opposite_type(bw), depth, -WIN_SCORE - 64, WIN_SCORE + 64, goal))
+    }).collect();
+    scores.sort_by_key(|(_, score)| -score);
+    scores
+}
+
+fn final_score<T>(bw: OccupyType, f: &T, goal: Goal) -> i32 where T: FieldAction {
+    let (w, b) = f.get_score_wb();
+    let diff = if bw == OccupyType::White { w as i32 - b as i32 } else { b as i32 - w as i32 };
+    let diff = if goal == Goal::FewestDiscs { -diff } else { diff };
+    if diff > 0 {
+        WIN_SCORE + diff
+    } else if diff < 0 {
+        -WIN_SCORE + diff
+    } else {
+        0
+    }
+}
+
+fn negamax<T>(f: &T, bw: OccupyType, depth: u8, mut alpha: i32, beta: i32, goal: Goal) -> i32 where T: FieldAction + Clone {
+    let moves = f.get_list_of_moves(bw);
+    let opposite = OccupyType::get_opposite_type(bw);
+    if moves.is_empty() {
+        if f.get_list_of_moves(opposite).is_empty() {
+            return final_score(bw, f, goal);
+        }
+        if depth == 0 {
+            return evaluate_goal(bw, f, goal);
+        }
+        return -negamax(f, opposite, depth - 1, -beta, -alpha, goal);
+    }
+    if depth == 0 {
+        return evaluate_goal(bw, f, goal);
+    }
+    for (p, _) in moves {
+        let mut next = f.clone();
+        next.move_in_game(&p, bw);
+        let score = -negamax(&next, opposite, depth - 1, -beta, -alpha, goal);
+        if score > alpha {
+            alpha = score;
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    alpha
+}
+
+
+#[test]
+fn engine_from_string_test() {
+    assert_eq!(Some(Engine::Corner), Engine::from_string("corner"));
+    assert_eq!(Some(Engine::Search(3)), Engine::from_string("Search:3"));
+    assert_eq!(None, Engine::from_string("search:0"));
+    assert_eq!(None, Engine::from_string("smart"));
+    assert_eq!("search:5", Engine::Search(5).to_string());
+}
+
+#[test]
+fn engine_takes_corner_test() {
+    let mut f = Field::new();
+    f.setup_field("
+
+o
+o
+o
+*oooo
+
+
+");
+    for engine in &[Engine::Corner, Engine::Search(1), Engine::Search(3)] {
+        assert_eq!(Some(Point::new(1, 8)), engine.get_move(OccupyType::Black, &f), "{}", engine);
+    }
+}
+
+#[test]
+fn engine_pass_test() {
+    let mut f = Field::new();
+    f.setup_field("
+**
+");
+    for engine in &[Engine::Random, Engine::Greedy, Engine::Corner, Engine::Search(2)] {
+        assert_eq!(None, engine.get_move(OccupyType::Black, &f));
+    }
+}
+
+#[test]
+fn engine_misere_test() {
+    let mut f = Field::new();
+    f.setup_field("
+
+o
+o
+o
+*oooo
+      o
+      *
+");
+    //the corner flips 3, (6,4) 4 and (7,4) 1
+    for engine in &[Engine::Greedy, Engine::Corner] {
+        let p = engine.get_move_goal(OccupyType::Black, &f, Goal::FewestDiscs).unwrap();
+        assert!(!p.is_corner(), "{}", engine);
+        assert_eq!(1, f.is_valid_move(&p, OccupyType::Black).1, "{}", engine);
+    }
+    assert_ne!(Some(Point::new(1, 8)), Engine::Search(1).get_move_goal(OccupyType::Black, &f, Goal::FewestDiscs));
+
+    //the final score counts for the side with fewer discs
+    let mut end = Field::new();
+    end.setup_field("
+*o
+");
+    assert!(final_score(OccupyType::Black, &end, Goal::FewestDiscs) == 0);
+    end.set_type(&Point::new(3, 8), OccupyType::White);
+    assert!(final_score(OccupyType::Black, &end, Goal::FewestDiscs) > WIN_SCORE);
+    assert!(final_score(OccupyType::Black, &end, Goal::MostDiscs) < -WIN_SCORE);
+}
+
+#[test]
+fn engine_greedy_test() {
+    let mut f = Field::new();
+    f.setup_field("
+
+o
+o
+o
+*oooo
+      o
+      *
+");
+    //the most flips win over the corner, which flips 3
+    let p = Engine::Greedy.get_move(OccupyType::Black, &f).unwrap();
+    assert!(!p.is_corner());
+    assert_eq!(4, f.is_valid_move(&p, OccupyType::Black).1);
+    assert_eq!(Some(Point::new(1, 8)), Engine::Corner.get_move(OccupyType::Black, &f));
+}
+
+#[test]
+fn engine_score_moves_test() {
+    let mut f = Field::new();
+    f.setup_field("
+
+o
+o
+o
+*oooo
+
+
+");
+    let scores = score_moves(OccupyType::Black, &f, 3, Goal::MostDiscs);
+    assert_eq!(Point::new(1, 8), scores[0].0);
+    assert!(scores.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert!(score_moves(OccupyType::Black, &Field::new(), 3, Goal::MostDiscs).is_empty());
+}
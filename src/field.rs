@@ -3,12 +3,16 @@ use std::fmt;
 
 use crate::point::{Point, BOARD_SIZE};
 use crate::occupytype::OccupyType;
+use crate::parser::parse_board;
+use crate::shape::Shape;
 
 
 
 #[derive(Debug, Clone)]
 pub struct Field {
     data: HashMap<Point, OccupyType>,
+    //the cells outside the shape read as `OccupyType::Blocked`
+    shape: Shape,
 }
 
 
@@ -22,6 +26,11 @@ pub trait FieldAction {
     fn move_in_game(&mut self, p: &Point, bw: OccupyType) -> u8;
     fn serialize(&self) -> String;
     fn deserialize(&mut self, s: &String);
+    //a cell which can never be flipped: on every line it has the board edge or a blocked cell next to it;
+    //the corners of the square board by default
+    fn is_corner(&self, p: &Point) -> bool {
+        p.is_corner()
+    }
 }
 
 
@@ -29,10 +38,32 @@ impl Field {
     pub fn new() -> Self {
         let field = Field {
             data: Default::default(),
+            shape: Shape::Square,
         };
         field
     }
 
+    pub fn with_shape(shape: Shape) -> Self {
+        let mut field = Field::new();
+        field.shape = shape;
+        field
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    //the discs outside the new shape are removed
+    pub fn set_shape(&mut self, shape: Shape) {
+        self.shape = shape;
+        self.data.retain(|p, _| shape.contains(p));
+    }
+
+    //`Point::check_point` for the shape of the field
+    pub fn check_point(&self, x: i8, y: i8) -> bool {
+        self.shape.check_point(x, y)
+    }
+
 
     pub fn to_string(&self) -> String {
         let mut ret = String::from("\n");
@@ -77,7 +108,10 @@ impl Field {
         format!("{}\n", ret)
     }
 
-    fn get_type(&self, p: &Point) -> OccupyType {
+    pub fn get_type(&self, p: &Point) -> OccupyType {
+        if !self.shape.contains(p) {
+            return OccupyType::Blocked;
+        }
         return match self.data.get(p) {
             Some(t) => (*t).clone(),
             None => OccupyType::Empty
@@ -85,6 +119,19 @@ impl Field {
     }
 
 
+    //places a disc or clears the point, no game rules are checked; the cells outside the shape are left as they are
+    pub fn set_type(&mut self, p: &Point, bw: OccupyType) {
+        if !self.shape.contains(p) {
+            return;
+        }
+        if bw == OccupyType::Empty {
+            self.data.remove(p);
+        } else {
+            self.data.insert(*p, bw);
+        }
+    }
+
+
     fn add(&mut self, p: &Point, bw: OccupyType) -> bool {
         if self.get_type(p) != OccupyType::Empty {
             panic!("Wrong move: Your cannot move to occupied point");
@@ -133,7 +180,8 @@ impl Field {
         }
     }
 
-    fn get_opposite_points(&self, p: &Point, color: OccupyType) -> Vec<Point> {
+    //discs flipped by the move of color to p
+    pub fn get_opposite_points(&self, p: &Point, color: OccupyType) -> Vec<Point> {
         let point_type = self.get_type(p);
         if point_type != OccupyType::Empty {
             return vec![];
@@ -149,7 +197,7 @@ impl Field {
                 loop {
                     let current_x: i8 = x as i8 + dx * step;
                     let current_y: i8 = y as i8 + dy * step;
-                    if !Point::check_point(current_x, current_y) {
+                    if !self.check_point(current_x, current_y) {
                         break;
                     }
                     let current_p = Point::new(current_x as u8, current_y as u8);
@@ -181,10 +229,13 @@ impl fmt::Display for Field {
 impl FieldAction for Field {
     fn init(&mut self) {
         let middle = BOARD_SIZE / 2;
-        self.add(&Point::new(middle, middle), OccupyType::Black);
-        self.add(&Point::new(middle + 1, middle + 1), OccupyType::Black);
-        self.add(&Point::new(middle, middle + 1), OccupyType::White);
-        self.add(&Point::new(middle + 1, middle), OccupyType::White);
+        let start = [((middle, middle), OccupyType::Black), ((middle + 1, middle + 1), OccupyType::Black),
+            ((middle, middle + 1), OccupyType::White), ((middle + 1, middle), OccupyType::White)];
+        //a mask shape may leave out some of the middle cells
+        let shape = self.shape;
+        for ((x, y), bw) in start.iter().filter(|((x, y), _)| shape.check_point(*x as i8, *y as i8)) {
+            self.add(&Point::new(*x, *y), *bw);
+        }
     }
 
 
@@ -215,7 +266,7 @@ impl FieldAction for Field {
                 white.push(*key);
             } else if t == OccupyType::Black {
                 black.push(*key);
-            } else {
+            } else if t != OccupyType::Blocked {
                 panic!("Wrong type for point {}", key.to_string());
             }
         };
@@ -285,25 +336,79 @@ impl FieldAction for Field {
         return ret;
     }
 
+    //accepts any notation of `parser::parse_board`, panics on a wrong string
     fn deserialize(&mut self, s: &String) {
-        if s.len() != (BOARD_SIZE * BOARD_SIZE) as usize {
-            panic!("Wrong size of string for the field deserialization")
+        let f = parse_board(s).unwrap_or_else(|e| panic!("Wrong string for the field deserialization: {}", e));
+        for (p, bw) in f.data {
+            //the holes of the shape are written as blocked cells
+            if bw == OccupyType::Blocked && !self.shape.contains(&p) {
+                continue;
+            }
+            self.add(&p, bw);
         }
+    }
+
+    fn is_corner(&self, p: &Point) -> bool {
+        let (x, y) = (p.x() as i8, p.y() as i8);
+        let wall = |x: i8, y: i8| !self.check_point(x, y) || self.get_type(&Point::new(x as u8, y as u8)) == OccupyType::Blocked;
+        self.shape.contains(p) && [(1, 0), (0, 1), (1, 1), (1, -1)].iter().all(|(dx, dy)| wall(x + dx, y + dy) || wall(x - dx, y - dy))
+    }
+}
+
 
-        let mut i: u8 = 0;
-        for (j, c) in s.chars().enumerate() {
-            let mut x:u8= (j + 1 - (BOARD_SIZE * i) as usize)as u8;
-            if x > BOARD_SIZE  {
-                x=1;
-                i=i+1;
+//serialized as {"rows": ["........", ...]}: row 8 first, `#` black, `O` white, `.` empty, `@` blocked;
+//a board which is not a full square adds its shape, e.g. "shape": "octagon:2"
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FieldData {
+    rows: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shape: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Field {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cells: Vec<char> = FieldAction::serialize(self).chars()
+            .map(|c| if c == ' ' { '.' } else { c })
+            .collect();
+        let rows = cells.chunks(BOARD_SIZE as usize).map(|row| row.iter().collect()).collect();
+        let shape = if self.shape == Shape::Square { None } else { Some(self.shape.to_string()) };
+        serde::Serialize::serialize(&FieldData { rows, shape }, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Field {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let data: FieldData = serde::Deserialize::deserialize(deserializer)?;
+        if data.rows.len() != BOARD_SIZE as usize {
+            return Err(D::Error::custom(format!("expected {} rows", BOARD_SIZE)));
+        }
+        let mut cells = String::new();
+        for row in &data.rows {
+            if row.chars().count() != BOARD_SIZE as usize {
+                return Err(D::Error::custom(format!("expected {} cells in row '{}'", BOARD_SIZE, row)));
+            }
+            for c in row.chars() {
+                match c {
+                    '#' | 'O' | '@' => cells.push(c),
+                    '.' => cells.push(' '),
+                    _ => return Err(D::Error::custom(format!("unexpected symbol '{}'", c))),
+                }
             }
-            let y: u8 = BOARD_SIZE - i;
-            self.add(&Point::new(x, y),OccupyType::from_string(&c.to_string()));
         }
+        let shape = match &data.shape {
+            Some(shape) => Shape::from_string(shape).map_err(D::Error::custom)?,
+            None => Shape::Square,
+        };
+        let mut f = Field::with_shape(shape);
+        f.deserialize(&cells);
+        Ok(f)
     }
 }
 
-
 #[test]
 fn field_setup_field_test() {
     let mut f = Field::new();
@@ -523,3 +628,99 @@ fn field_serialize_field_test() {
     let b=f.to_string();
     assert_eq!(a, b);
 }
+
+
+#[test]
+fn field_deserialize_notations_test() {
+    let mut f = Field::new();
+    f.deserialize(&"---------------------------OX------XO---------------------------".to_string());
+    let mut g = Field::new();
+    g.init();
+    assert_eq!(g.serialize(), f.serialize());
+    //no empty points are stored
+    let (w, b) = f.get_position_wb();
+    assert_eq!(4, w.len() + b.len());
+}
+
+#[test]
+fn field_blocked_test() {
+    let mut f = Field::new();
+    f.setup_field("
+*o
+*oo
+*.o
+");
+    f.set_type(&Point::new(3, 8), OccupyType::Blocked);
+    f.set_type(&Point::new(2, 6), OccupyType::Blocked);
+    //no disc on a blocked cell and no flipping over it
+    assert!(!f.is_valid_move(&Point::new(3, 8), OccupyType::Black).0);
+    assert!(!f.is_valid_move(&Point::new(4, 6), OccupyType::Black).0);
+    assert_eq!(vec![(Point::new(4, 5), 2), (Point::new(4, 7), 2)], f.get_list_of_moves(OccupyType::Black));
+    assert_eq!((4, 3), f.get_score_wb());
+
+    let s = f.serialize();
+    assert!(s.starts_with("#O@     #OO     #@O"));
+    let mut g = Field::new();
+    g.deserialize(&s);
+    assert_eq!(OccupyType::Blocked, g.get_type(&Point::new(2, 6)));
+    assert_eq!(s, g.serialize());
+}
+
+#[test]
+fn field_octagon_test() {
+    let mut f = Field::with_shape(Shape::Octagon(2));
+    f.init();
+    assert_eq!(OccupyType::Blocked, f.get_type(&Point::new(1, 2)));
+    f.set_type(&Point::new(1, 1), OccupyType::Black);
+    assert_eq!((2, 2), f.get_score_wb());
+    assert!(!f.check_point(2, 1) && f.check_point(3, 1));
+    assert!(f.is_corner(&Point::new(3, 1)) && f.is_corner(&Point::new(1, 6)));
+    assert!(!f.is_corner(&Point::new(1, 1)) && !f.is_corner(&Point::new(4, 1)));
+
+    //no move to the clipped corner
+    let mut g = Field::new();
+    g.setup_field("
+
+ o
+  *
+");
+    assert_eq!(vec![(Point::new(1, 8), 1)], g.get_list_of_moves(OccupyType::Black));
+    g.set_shape(Shape::Octagon(2));
+    assert!(g.get_list_of_moves(OccupyType::Black).is_empty());
+
+    let s = g.serialize();
+    assert!(s.starts_with("@@    @@@"));
+    let mut h = Field::with_shape(Shape::Octagon(2));
+    h.deserialize(&s);
+    assert_eq!(s, h.serialize());
+}
+
+#[test]
+#[should_panic(expected = "unexpected symbol")]
+fn field_deserialize_wrong_test() {
+    let mut f = Field::new();
+    f.deserialize(&"?".repeat(64));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn field_serde_test() {
+    let mut f = Field::new();
+    f.init();
+    let json = serde_json::to_string(&f).unwrap();
+    assert_eq!(r#"{"rows":["........","........","........","...O#...","...#O...","........","........","........"]}"#, json);
+    let g: Field = serde_json::from_str(&json).unwrap();
+    assert_eq!(f.serialize(), g.serialize());
+    assert!(serde_json::from_str::<Field>(r#"{"rows":["........"]}"#).is_err());
+
+    //the shape is kept
+    let mut f = Field::with_shape(Shape::Octagon(2));
+    f.init();
+    let json = serde_json::to_string(&f).unwrap();
+    assert!(json.ends_with(r#""shape":"octagon:2"}"#), "{}", json);
+    let g: Field = serde_json::from_str(&json).unwrap();
+    assert_eq!(Shape::Octagon(2), g.shape());
+    assert_eq!(f.serialize(), g.serialize());
+    assert!(!g.check_point(1, 1));
+    assert!(serde_json::from_str::<Field>(&json.replace("octagon:2", "circle")).is_err());
+}
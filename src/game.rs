@@ -9,7 +9,7 @@ pub fn predict_player_corner<T: Clone>(computer: OccupyType, mv: &Point, mut f:
     let moves = f.get_list_of_moves(OccupyType::get_opposite_type(computer));
 
     for (p, _) in moves {
-        if p.is_corner() {
+        if f.is_corner(&p) {
             return true;
         }
     }
@@ -31,11 +31,11 @@ pub fn computer_get_best_moves<T: Clone>(check_corner: bool, bw: OccupyType, mov
                 init = false;
                 max_point = vec![*p];
                 max = *n;
-                if p.is_corner() {
+                if f.is_corner(p) {
                     corner = true;
                 }
             }
-            if p.is_corner() {
+            if f.is_corner(p) {
                 if corner {
                     if *n > max {
                         max_point = vec![*p];
@@ -61,18 +61,37 @@ pub fn computer_get_best_moves<T: Clone>(check_corner: bool, bw: OccupyType, mov
     return max_point;
 }
 
-pub fn computer_move<T: Clone>(bw: OccupyType, mut f: T) -> T where T: FieldAction {
+//the moves which flip the fewest discs, for misère; corners (stable discs) only if there is nothing else
+pub fn computer_get_fewest_moves<T>(avoid_corners: bool, moves: &[(Point, u8)], f: &T) -> Vec<Point> where T: FieldAction {
+    let candidates: Vec<&(Point, u8)> = if avoid_corners && moves.iter().any(|(p, _)| !f.is_corner(p)) {
+        moves.iter().filter(|(p, _)| !f.is_corner(p)).collect()
+    } else {
+        moves.iter().collect()
+    };
+    let min = candidates.iter().map(|(_, n)| *n).min();
+    candidates.into_iter().filter(|(_, n)| Some(*n) == min).map(|(p, _)| *p).collect()
+}
+
+//picks one of the best moves at random, None if there is no move (pass)
+pub fn computer_get_move<T>(bw: OccupyType, f: &T) -> Option<Point> where T: FieldAction + Clone {
     let moves = f.get_list_of_moves(bw);
 
     let mut max_point = computer_get_best_moves(true, bw, &moves, f.clone());
-    if max_point.len() == 0 {
+    if max_point.is_empty() {
         max_point = computer_get_best_moves(false, bw, &moves, f.clone());
     }
+    if max_point.is_empty() {
+        return None;
+    }
     let mut rng = rand::thread_rng();
     let idx = rng.gen_range(0..max_point.len());
-    println!("Options: {}, selected: {}", max_point.len(), idx);
-    let changed = f.move_in_game(&max_point[idx], bw);
-    println!("Computer has moved to {}, +{} score", max_point[idx], changed + 1);
+    Some(max_point[idx])
+}
+
+pub fn computer_move<T: Clone>(bw: OccupyType, mut f: T) -> T where T: FieldAction {
+    let p = computer_get_move(bw, &f).expect("Computer has no move");
+    let changed = f.move_in_game(&p, bw);
+    println!("Computer has moved to {}, +{} score", p, changed + 1);
 
     return f;
 }
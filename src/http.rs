@@ -0,0 +1,238 @@
+/*
+An analysis service over HTTP (the `http` feature), run by the `rustversi-http` binary, so that tools in
+other languages can use the engine. Every endpoint is a POST with a JSON body of a position: the board as
+in serde (`{"rows": [...]}`, row 8 first) and the side to move.
+
+  POST /moves      {"board": {...}, "to_move": "black"}
+                   -> {"moves": [{"x": 4, "y": 3, "flips": 1}, ...]}
+  POST /apply      {"board": {...}, "to_move": "black", "moves": [{"x": 4, "y": 3}, null]}
+                   -> {"board": {...}, "to_move": "white", "score": {"white": 1, "black": 4}}
+                      null is a pass, allowed only without a legal move, the passes are optional as in
+                      `rustversi apply`; to_move of the answer is the side with a legal move, null at the end
+  POST /best       {"board": {...}, "to_move": "black", "engine": "search:4"}
+                   -> {"move": {"x": 4, "y": 3}}
+                      engine is the level of the computer player (`engine::Engine`, "corner" by default),
+                      the move is null for a pass
+  POST /evaluate   {"board": {...}, "to_move": "black"}
+                   -> {"evaluation": 12, "score": {"white": 2, "black": 2}}
+                      `engine::evaluate` from the point of view of to_move
+
+An error is {"error": "..."} with the status 400 (a wrong request), 404 or 405.
+A pool of threads serves the requests, `HttpServer::bind` takes its size.
+*/
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tiny_http::{Header, Method, Response};
+
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::game::possible_movement;
+use crate::engine::{self, Engine};
+
+
+pub const DEFAULT_THREADS: usize = 4;
+
+
+#[derive(serde::Deserialize)]
+struct Position {
+    board: Field,
+    to_move: OccupyType,
+}
+
+#[derive(serde::Deserialize)]
+struct ApplyRequest {
+    #[serde(flatten)]
+    position: Position,
+    moves: Vec<Option<Point>>,
+}
+
+#[derive(serde::Deserialize)]
+struct BestRequest {
+    #[serde(flatten)]
+    position: Position,
+    #[serde(default)]
+    engine: Engine,
+}
+
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|e| format!("wrong request: {}", e))
+}
+
+fn check_side(bw: OccupyType) -> Result<OccupyType, String> {
+    match bw {
+        OccupyType::White | OccupyType::Black => Ok(bw),
+        _ => Err("to_move must be white or black".to_string()),
+    }
+}
+
+fn score(f: &Field) -> serde_json::Value {
+    let (white, black) = f.get_score_wb();
+    json!({"white": white, "black": black})
+}
+
+fn moves(body: &str) -> Result<serde_json::Value, String> {
+    let position: Position = parse(body)?;
+    let bw = check_side(position.to_move)?;
+    let moves: Vec<_> = position.board.get_list_of_moves(bw).iter()
+        .map(|(p, n)| json!({"x": p.x(), "y": p.y(), "flips": n}))
+        .collect();
+    Ok(json!({"moves": moves}))
+}
+
+fn apply(body: &str) -> Result<serde_json::Value, String> {
+    let request: ApplyRequest = parse(body)?;
+    let mut f = request.position.board;
+    let mut bw = check_side(request.position.to_move)?;
+    for m in &request.moves {
+        if !possible_movement(bw, &f) {
+            bw = OccupyType::get_opposite_type(bw);
+            if m.is_none() {
+                continue;
+            }
+        }
+        let p = m.ok_or_else(|| format!("{} cannot pass, it has a legal move", bw))?;
+        let (valid, _) = f.is_valid_move(&p, bw);
+        if !valid {
+            return Err(format!("{} is not a valid move for {}", p, bw));
+        }
+        f.move_in_game(&p, bw);
+        bw = OccupyType::get_opposite_type(bw);
+    }
+    let to_move = [bw, OccupyType::get_opposite_type(bw)].iter().copied().find(|bw| possible_movement(*bw, &f));
+    Ok(json!({"board": f, "to_move": to_move, "score": score(&f)}))
+}
+
+fn best(body: &str) -> Result<serde_json::Value, String> {
+    let request: BestRequest = parse(body)?;
+    let bw = check_side(request.position.to_move)?;
+    Ok(json!({"move": request.engine.get_move(bw, &request.position.board)}))
+}
+
+fn evaluate(body: &str) -> Result<serde_json::Value, String> {
+    let position: Position = parse(body)?;
+    let bw = check_side(position.to_move)?;
+    Ok(json!({"evaluation": engine::evaluate(bw, &position.board), "score": score(&position.board)}))
+}
+
+//the status and the JSON body of the answer
+pub fn respond(method: &str, path: &str, body: &str) -> (u16, String) {
+    let endpoint: fn(&str) -> Result<serde_json::Value, String> = match path {
+        "/moves" => moves,
+        "/apply" => apply,
+        "/best" => best,
+        "/evaluate" => evaluate,
+        _ => return (404, json!({"error": format!("unknown endpoint '{}'", path)}).to_string()),
+    };
+    if method != "POST" {
+        return (405, json!({"error": format!("{} needs POST", path)}).to_string());
+    }
+    match endpoint(body) {
+        Ok(value) => (200, value.to_string()),
+        Err(e) => (400, json!({"error": e}).to_string()),
+    }
+}
+
+
+fn serve(server: &tiny_http::Server) {
+    let content_type = Header::from_bytes("Content-Type", "application/json").expect("a valid header");
+    while let Ok(mut request) = server.recv() {
+        let mut body = String::new();
+        let (status, answer) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                let method = if *request.method() == Method::Post { "POST" } else { "" };
+                //the query is ignored
+                let path = request.url().split('?').next().unwrap_or_default();
+                respond(method, path, &body)
+            }
+            Err(_) => (400, json!({"error": "the body is not UTF-8"}).to_string()),
+        };
+        let response = Response::from_string(answer).with_status_code(status).with_header(content_type.clone());
+        //the client may be gone already
+        let _ = request.respond(response);
+    }
+}
+
+
+pub struct HttpServer {
+    server: Arc<tiny_http::Server>,
+    threads: usize,
+}
+
+impl HttpServer {
+    //"0.0.0.0:8080", port 0 for any free one; threads is the size of the pool (at least 1)
+    pub fn bind(addr: &str, threads: usize) -> io::Result<HttpServer> {
+        let server = tiny_http::Server::http(addr).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        Ok(HttpServer { server: Arc::new(server), threads: threads.max(1) })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.server.server_addr().to_ip().ok_or_else(|| io::Error::new(io::ErrorKind::Other, "not an IP address"))
+    }
+
+    //serves the requests until the listener fails
+    pub fn run(self) {
+        let workers: Vec<_> = (0..self.threads).map(|_| {
+            let server = self.server.clone();
+            std::thread::spawn(move || serve(&server))
+        }).collect();
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+}
+
+
+#[cfg(test)]
+const HTTP_START: &str = r#"{"rows": ["........", "........", "........", "...#O...", "...O#...", "........", "........", "........"]}"#;
+
+#[test]
+fn http_respond_test() {
+    let position = format!(r#"{{"board": {}, "to_move": "black"}}"#, HTTP_START);
+    let (status, body) = respond("POST", "/moves", &position);
+    assert_eq!(200, status);
+    let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(4, value["moves"].as_array().unwrap().len());
+    assert_eq!(1, value["moves"][0]["flips"]);
+
+    let (status, body) = respond("POST", "/evaluate", &position);
+    assert_eq!(200, status);
+    assert!(body.contains(r#""score":{"black":2,"white":2}"#));
+
+    let best = |engine: &str| respond("POST", "/best", &format!(r#"{{"board": {}, "to_move": "black", "engine": "{}"}}"#, HTTP_START, engine));
+    let (status, body) = best("search:2");
+    assert_eq!(200, status, "{}", body);
+    let value: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(value["move"]["x"].is_u64());
+
+    assert_eq!(404, respond("POST", "/play", &position).0);
+    assert_eq!(405, respond("GET", "/moves", &position).0);
+    assert_eq!(400, respond("POST", "/moves", "{}").0);
+    assert_eq!(400, respond("POST", "/moves", &position.replace("black", "empty")).0);
+    assert_eq!(400, best("deep").0);
+}
+
+#[test]
+fn http_apply_test() {
+    let apply = |moves: &str| {
+        let (status, body) = respond("POST", "/apply", &format!(r#"{{"board": {}, "to_move": "black", "moves": {}}}"#, HTTP_START, moves));
+        (status, serde_json::from_str::<serde_json::Value>(&body).unwrap())
+    };
+    let (status, value) = apply(r#"[{"x": 4, "y": 3}, {"x": 3, "y": 3}]"#);
+    assert_eq!(200, status);
+    assert_eq!("black", value["to_move"]);
+    assert_eq!(serde_json::json!({"white": 3, "black": 3}), value["score"]);
+    assert_eq!("..O#....", value["board"]["rows"][5]);
+
+    let (status, value) = apply(r#"[{"x": 1, "y": 1}]"#);
+    assert_eq!(400, status);
+    assert!(value["error"].as_str().unwrap().contains("is not a valid move"));
+    let (status, value) = apply("[null]");
+    assert_eq!(400, status);
+    assert_eq!("# cannot pass, it has a legal move", value["error"]);
+}
@@ -2,22 +2,62 @@ use crate::point::{Point};
 use crate::occupytype::OccupyType;
 use crate::field::{Field, FieldAction};
 use crate::game::{computer_move, possible_movement};
+use crate::diagram::{Diagram, parse_arrow};
+use crate::render::Style;
+use crate::replay::Replay;
+use crate::rules::Rules;
+use crate::engine::Engine;
+use crate::clock::{Clock, ManualClock, TimeControl};
+use crate::puzzle::{Answer, Puzzle, PuzzleSession};
 use wasm_bindgen::prelude::*;
 
 pub mod point;
 pub mod occupytype;
 pub mod field;
 pub mod game;
+pub mod engine;
+pub mod savegame;
+pub mod parser;
+pub mod obf;
+pub mod wthor;
+pub mod record;
+pub mod diagram;
+pub mod render;
+pub mod symmetry;
+pub mod posdb;
+pub mod replay;
+pub mod rules;
+pub mod shape;
+pub mod multi;
+pub mod clock;
+//subprocesses and stdin/stdout, not for the browser
+#[cfg(not(target_arch = "wasm32"))]
+pub mod protocol;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod arena;
+pub mod puzzle;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "http")]
+pub mod http;
 
 
 #[wasm_bindgen]
 pub fn js_print_field_and_score(comp: &str, field_str: &str, win: bool) ->String {
+    js_print_field_and_score_style(comp, field_str, win, "classic")
+}
+
+//style is a name of `render::Style` ("classic" for an unknown one), "annotated" marks the moves of the player
+#[wasm_bindgen]
+pub fn js_print_field_and_score_style(comp: &str, field_str: &str, win: bool, style: &str) ->String {
 
     let computer = OccupyType::from_string(&comp.to_string());
     let mut f = Field::new();
     f.deserialize(&field_str.to_string());
 
-    let mut ret_value=format!("{}\n", f.to_string());
+    let style = Style::from_string(style).unwrap_or_default();
+    let player = if computer == OccupyType::White { OccupyType::Black } else { OccupyType::White };
+    let mut ret_value=format!("{}\n", style.render(&f, player));
     let score_c;
     let score_p;
     let (w, b) = f.get_score_wb();
@@ -64,6 +104,12 @@ pub fn js_field_init() -> String {
     return f.serialize();
 }
 
+//the start position of a variant (`rules::Rules::from_string`), an empty string for unknown rules
+#[wasm_bindgen]
+pub fn js_field_init_rules(rules: &str) -> String {
+    Rules::from_string(rules).map_or(String::new(), |r| r.field().serialize())
+}
+
 #[wasm_bindgen]
 pub fn js_computer_move(computer: &str, field_str: &str) -> String {
     let mut f = Field::new();
@@ -72,6 +118,28 @@ pub fn js_computer_move(computer: &str, field_str: &str) -> String {
     return f.serialize();
 }
 
+//the computer move under the rules (the misère goal), unknown rules are the standard ones
+#[wasm_bindgen]
+pub fn js_computer_move_rules(computer: &str, field_str: &str, rules: &str) -> String {
+    let mut f = Field::new();
+    f.deserialize(&field_str.to_string());
+    let rules = Rules::from_string(rules).unwrap_or_default();
+    let bw = OccupyType::from_string(&computer.to_string());
+    if let Some(p) = Engine::default().get_move_goal(bw, &f, rules.goal()) {
+        f.move_in_game(&p, bw);
+    }
+    f.serialize()
+}
+
+//the symbol of the winner under the rules, an empty string for a draw
+#[wasm_bindgen]
+pub fn js_winner_rules(field_str: &str, rules: &str) -> String {
+    let mut f = Field::new();
+    f.deserialize(&field_str.to_string());
+    let rules = Rules::from_string(rules).unwrap_or_default();
+    rules.winner(&f).map_or(String::new(), |bw| bw.to_string())
+}
+
 #[wasm_bindgen]
 pub fn js_possible_movement(jbw: &str, field_str: &str) -> bool {
     let mut f = Field::new();
@@ -130,6 +198,182 @@ pub fn js_get_score_pc(comp: &str,field_str: &str)->String{
     return format!("{}:{}",score_p,score_c);
 }
 
+//SVG diagram: last_move "x,y", legal the symbol of the side, moves "x,y x,y ..." to number,
+//arrows "x,y:x,y ..."; empty strings (and values which cannot be parsed) are skipped
+#[wasm_bindgen]
+pub fn js_render_svg(field_str: &str, coordinates: bool, last_move: &str, legal: &str, moves: &str, arrows: &str) -> String {
+    let mut f = Field::new();
+    f.deserialize(&field_str.to_string());
+    let mut d = Diagram::new();
+    d.coordinates = coordinates;
+    d.last_move = Point::from_string(last_move);
+    d.legal_moves = OccupyType::side_from_string(legal);
+    let moves: Vec<Option<Point>> = moves.split_whitespace().map(Point::from_string).collect();
+    d.number_moves(&moves);
+    d.arrows = arrows.split_whitespace().filter_map(parse_arrow).collect();
+    d.to_svg(&f)
+}
+
+//game review: moves "x,y x,y pass ..." from the initial position, first the symbol of the side which moved first;
+//the ply 0 is the start, a wrong move list or ply gives an empty string (-1 for the length)
+fn js_replay(moves: &str, first: &str, ply: u32) -> Option<Replay> {
+    let moves = moves.split_whitespace()
+        .map(|m| if m == "pass" { Some(None) } else { Point::from_string(m).map(Some) })
+        .collect::<Option<Vec<_>>>()?;
+    let mut r = Replay::from_moves(&moves, OccupyType::side_from_string(first)?).ok()?;
+    if r.jump(ply as usize) { Some(r) } else { None }
+}
+
+#[wasm_bindgen]
+pub fn js_replay_length(moves: &str, first: &str) -> i32 {
+    js_replay(moves, first, 0).map_or(-1, |r| r.len() as i32)
+}
+
+#[wasm_bindgen]
+pub fn js_replay_board(moves: &str, first: &str, ply: u32) -> String {
+    js_replay(moves, first, ply).map_or(String::new(), |r| r.field().serialize())
+}
+
+//"black:white"
+#[wasm_bindgen]
+pub fn js_replay_score(moves: &str, first: &str, ply: u32) -> String {
+    js_replay(moves, first, ply).map_or(String::new(), |r| {
+        let (w, b) = r.score();
+        format!("{}:{}", b, w)
+    })
+}
+
+//"x,y", "pass" or "" for the ply 0
+#[wasm_bindgen]
+pub fn js_replay_move(moves: &str, first: &str, ply: u32) -> String {
+    let r = match js_replay(moves, first, ply) {
+        Some(r) => r,
+        None => return String::new(),
+    };
+    match r.last_ply() {
+        Some(ply) => ply.mv.map_or("pass".to_string(), |p| format!("{},{}", p.x(), p.y())),
+        None => String::new(),
+    }
+}
+
+//the discs flipped by the move of the ply, "x,y x,y ..."
+#[wasm_bindgen]
+pub fn js_replay_flips(moves: &str, first: &str, ply: u32) -> String {
+    js_replay(moves, first, ply).map_or(String::new(), |r| {
+        let flips: Vec<String> = r.flips().iter().map(|p| format!("{},{}", p.x(), p.y())).collect();
+        flips.join(" ")
+    })
+}
+
+//game clocks: the state string of `clock::Clock::state` goes from call to call, now_ms is `Date.now()`;
+//a wrong state or control gives an empty string
+fn js_clock(state: &str, now_ms: f64) -> Option<Clock<ManualClock>> {
+    Clock::from_state(state, ManualClock::new(now_ms as u64)).ok()
+}
+
+//the clock of a time control (`clock::TimeControl::from_string`), the time of the first side is running
+#[wasm_bindgen]
+pub fn js_clock_new(control: &str, first: &str, now_ms: f64) -> String {
+    let control = match TimeControl::from_string(control) {
+        Ok(control) => control,
+        Err(_) => return String::new(),
+    };
+    let first = match OccupyType::side_from_string(first) {
+        Some(first) => first,
+        None => return String::new(),
+    };
+    let mut clock = Clock::new(control, ManualClock::new(now_ms as u64));
+    clock.start(first);
+    clock.state()
+}
+
+//the side to move has moved (or passed), the time of the other side is running
+#[wasm_bindgen]
+pub fn js_clock_press(state: &str, now_ms: f64) -> String {
+    js_clock(state, now_ms).map_or(String::new(), |mut clock| {
+        clock.press();
+        clock.state()
+    })
+}
+
+//the time the side to move has used on this move so far, -1 for a wrong state
+#[wasm_bindgen]
+pub fn js_clock_used_ms(state: &str, now_ms: f64) -> f64 {
+    js_clock(state, now_ms).map_or(-1.0, |mut clock| clock.stop() as f64)
+}
+
+//the remaining time of the side as "4:32", "0:07.4 (2x)" in byo-yomi
+#[wasm_bindgen]
+pub fn js_clock_time(state: &str, side: &str, now_ms: f64) -> String {
+    match (js_clock(state, now_ms), OccupyType::side_from_string(side)) {
+        (Some(clock), Some(bw)) => clock.side(bw).to_string(),
+        _ => String::new(),
+    }
+}
+
+//the symbol of the side which has lost on time, an empty string if none has
+#[wasm_bindgen]
+pub fn js_clock_flagged(state: &str, now_ms: f64) -> String {
+    js_clock(state, now_ms).and_then(|clock| clock.flagged()).map_or(String::new(), |bw| bw.to_string())
+}
+
+//puzzles: the text of a puzzle file (`puzzle::read_all`) and the number of the puzzle from 0 go from call
+//to call, like the replay the answers of the player so far are passed every time ("x,y" or "pass", space separated)
+fn js_puzzle(text: &str, n: u32) -> Option<Puzzle> {
+    puzzle::read_all(text).ok()?.into_iter().nth(n as usize)
+}
+
+fn js_point(p: Option<Point>) -> String {
+    p.map_or("pass".to_string(), |p| format!("{},{}", p.x(), p.y()))
+}
+
+//the session after the answers and the grade of the last one
+fn js_puzzle_session<'a>(p: &'a Puzzle, answers: &str) -> Option<(PuzzleSession<'a>, String)> {
+    let mut session = PuzzleSession::new(p);
+    let mut grade = String::new();
+    for a in answers.split_whitespace() {
+        let mv = if a == "pass" { None } else { Some(Point::from_string(a)?) };
+        grade = match session.answer(mv) {
+            Answer::Illegal => "illegal".to_string(),
+            Answer::Wrong(expected) => format!("wrong {}", js_point(expected)),
+            Answer::Correct(Some(reply)) => format!("correct {}", js_point(reply)),
+            Answer::Correct(None) => "solved".to_string(),
+        };
+    }
+    Some((session, grade))
+}
+
+//-1 for a wrong file
+#[wasm_bindgen]
+pub fn js_puzzle_count(text: &str) -> i32 {
+    puzzle::read_all(text).map_or(-1, |puzzles| puzzles.len() as i32)
+}
+
+//the title, the goal if there is none
+#[wasm_bindgen]
+pub fn js_puzzle_title(text: &str, n: u32) -> String {
+    js_puzzle(text, n).map_or(String::new(), |p| if p.title.is_empty() { p.goal.to_string() } else { p.title })
+}
+
+//the board after the answers and the replies
+#[wasm_bindgen]
+pub fn js_puzzle_board(text: &str, n: u32, answers: &str) -> String {
+    js_puzzle(text, n).and_then(|p| js_puzzle_session(&p, answers).map(|(s, _)| s.field().serialize())).unwrap_or_default()
+}
+
+//the symbol of the player
+#[wasm_bindgen]
+pub fn js_puzzle_to_move(text: &str, n: u32) -> String {
+    js_puzzle(text, n).map_or(String::new(), |p| p.to_move.to_string())
+}
+
+//the grade of the last answer: "illegal", "wrong <solution>", "correct <reply>" or "solved";
+//an empty string for a wrong puzzle or answer
+#[wasm_bindgen]
+pub fn js_puzzle_grade(text: &str, n: u32, answers: &str) -> String {
+    js_puzzle(text, n).and_then(|p| js_puzzle_session(&p, answers).map(|(_, grade)| grade)).unwrap_or_default()
+}
+
 /* 
 ////////////////////////////////////////////////////////////////////////////////
 JS usage example (../node/nodever.js)
@@ -233,3 +477,14 @@ RUST usage example: see ./bin/rustversi.rs
 
 */
 
+
+#[test]
+fn js_replay_test() {
+    assert_eq!(2, js_replay_length("4,6 3,4", "#"));
+    assert_eq!("4,6", js_replay_move("4,6 3,4", "#", 1));
+    //the side which moved first must be a player
+    for first in ["x", " ", "@", ""] {
+        assert_eq!(-1, js_replay_length("4,6 3,4", first));
+        assert_eq!("", js_replay_board("4,6 3,4", first, 1));
+    }
+}
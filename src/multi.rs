@@ -0,0 +1,446 @@
+/*
+Reversi for 2 to 4 players on a square board of up to `MAX_SIZE` cells. The players move in turn
+(`#`, `O`, `+`, `%`); a move flips the discs of any other colours bracketed by the discs of the mover.
+A player without a legal move passes and the turn goes on to the following one, the game is over when
+nobody can move. The most discs win, several players may share the win.
+
+The start is a `players` x `players` square in the middle: colour (dx + dy) % players at the offset
+(dx, dy) from its bottom left cell, so for two players it is the standard start of `FieldAction::init`.
+The default boards are 8x8, 9x9 and 10x10 for 2, 3 and 4 players.
+
+    let mut game = MultiGame::new(3)?;
+    while let Some(colour) = game.to_move() {
+        let (x, y) = multi_computer_move(game.field(), colour).unwrap();
+        game.play(x, y)?;
+    }
+    println!("{:?}", game.winners());
+
+The cells are (x, y) as for `Point`: 1..size, y = size is the top row; `Point` is limited to the
+8x8 board, so the module has its own coordinates.
+*/
+use core::fmt;
+use rand::prelude::*;
+
+use crate::occupytype::OccupyType;
+
+
+pub const MIN_PLAYERS: u8 = 2;
+pub const MAX_PLAYERS: u8 = 4;
+pub const MAX_SIZE: u8 = 16;
+const SYMBOLS: [char; MAX_PLAYERS as usize] = ['#', 'O', '+', '%'];
+const DIRECTIONS: [(i8, i8); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+
+//a player: 0 moves first, `#` and `O` are black and white of the two-player game
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Colour(u8);
+
+impl Colour {
+    pub fn new(index: u8) -> Colour {
+        if index >= MAX_PLAYERS {
+            panic!("No colour {} of {}", index, MAX_PLAYERS);
+        }
+        Colour(index)
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    pub fn symbol(&self) -> char {
+        SYMBOLS[self.0 as usize]
+    }
+
+    pub fn from_symbol(c: char) -> Option<Colour> {
+        SYMBOLS.iter().position(|s| *s == c).map(|i| Colour(i as u8))
+    }
+
+    pub fn from_occupy(t: OccupyType) -> Option<Colour> {
+        match t {
+            OccupyType::Black => Some(Colour(0)),
+            OccupyType::White => Some(Colour(1)),
+            _ => None,
+        }
+    }
+
+    //the following player in the turn order
+    pub fn following(&self, players: u8) -> Colour {
+        Colour((self.0 + 1) % players)
+    }
+}
+
+impl fmt::Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiField {
+    size: u8,
+    players: u8,
+    //row by row from y = 1
+    cells: Vec<Option<Colour>>,
+}
+
+impl MultiField {
+    //an empty board
+    pub fn new(size: u8, players: u8) -> Result<MultiField, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) {
+            return Err(format!("wrong number of players {}, expected {}..{}", players, MIN_PLAYERS, MAX_PLAYERS));
+        }
+        if size < players + 2 || size > MAX_SIZE || (size - players) % 2 != 0 {
+            return Err(format!("wrong board size {} for {} players, expected {}..{} of the same parity",
+                               size, players, players + 2, MAX_SIZE));
+        }
+        Ok(MultiField { size, players, cells: vec![None; size as usize * size as usize] })
+    }
+
+    //the board with the start square in the middle
+    pub fn start(size: u8, players: u8) -> Result<MultiField, String> {
+        let mut f = MultiField::new(size, players)?;
+        let first = (size - players) / 2 + 1;
+        for dy in 0..players {
+            for dx in 0..players {
+                f.set(first + dx, first + dy, Some(Colour((dx + dy) % players)));
+            }
+        }
+        Ok(f)
+    }
+
+    pub fn default_size(players: u8) -> u8 {
+        players + 6
+    }
+
+    pub fn size(&self) -> u8 {
+        self.size
+    }
+
+    pub fn players(&self) -> u8 {
+        self.players
+    }
+
+    pub fn colours(&self) -> Vec<Colour> {
+        (0..self.players).map(Colour).collect()
+    }
+
+    pub fn check(&self, x: i8, y: i8) -> bool {
+        x >= 1 && y >= 1 && x <= self.size as i8 && y <= self.size as i8
+    }
+
+    fn index(&self, x: u8, y: u8) -> usize {
+        if !self.check(x as i8, y as i8) {
+            panic!("Cell ({},{}) is out of the board {}x{}", x, y, self.size, self.size);
+        }
+        (y as usize - 1) * self.size as usize + x as usize - 1
+    }
+
+    pub fn get(&self, x: u8, y: u8) -> Option<Colour> {
+        self.cells[self.index(x, y)]
+    }
+
+    //places a disc or clears the cell, no game rules are checked
+    pub fn set(&mut self, x: u8, y: u8, c: Option<Colour>) {
+        let i = self.index(x, y);
+        self.cells[i] = c;
+    }
+
+    //discs flipped by the move of colour to (x, y)
+    pub fn flips(&self, x: u8, y: u8, colour: Colour) -> Vec<(u8, u8)> {
+        if self.get(x, y).is_some() {
+            return vec![];
+        }
+        let mut result = vec![];
+        for (dx, dy) in DIRECTIONS.iter() {
+            let mut line = vec![];
+            let (mut cx, mut cy) = (x as i8 + dx, y as i8 + dy);
+            while self.check(cx, cy) {
+                match self.get(cx as u8, cy as u8) {
+                    Some(c) if c == colour => {
+                        result.append(&mut line);
+                        break;
+                    }
+                    Some(_) => line.push((cx as u8, cy as u8)),
+                    None => break,
+                }
+                cx += dx;
+                cy += dy;
+            }
+        }
+        result
+    }
+
+    //((x, y), flips) of every legal move
+    pub fn moves(&self, colour: Colour) -> Vec<((u8, u8), u8)> {
+        let mut result = vec![];
+        for y in 1..=self.size {
+            for x in 1..=self.size {
+                let n = self.flips(x, y, colour).len();
+                if n > 0 {
+                    result.push(((x, y), n as u8));
+                }
+            }
+        }
+        result
+    }
+
+    //the number of the flipped discs
+    pub fn play(&mut self, x: u8, y: u8, colour: Colour) -> Result<u8, String> {
+        if !self.check(x as i8, y as i8) {
+            return Err(format!("({},{}) is out of the board", x, y));
+        }
+        let flips = self.flips(x, y, colour);
+        if flips.is_empty() {
+            return Err(format!("({},{}) is not a valid move for {}", x, y, colour));
+        }
+        for (fx, fy) in &flips {
+            self.set(*fx, *fy, Some(colour));
+        }
+        self.set(x, y, Some(colour));
+        Ok(flips.len() as u8)
+    }
+
+    //the number of discs of every colour, by `Colour::index`
+    pub fn scores(&self) -> Vec<u8> {
+        let mut ret = vec![0; self.players as usize];
+        for c in self.cells.iter().flatten() {
+            ret[c.0 as usize] += 1;
+        }
+        ret
+    }
+
+    pub fn is_corner(&self, x: u8, y: u8) -> bool {
+        (x == 1 || x == self.size) && (y == 1 || y == self.size)
+    }
+
+    //the cells next to an empty corner give the corner away
+    fn near_empty_corner(&self, x: u8, y: u8) -> bool {
+        [1, self.size].iter().any(|cx| [1, self.size].iter().any(|cy| {
+            self.get(*cx, *cy).is_none() && (x, y) != (*cx, *cy)
+                && (x as i8 - *cx as i8).abs() <= 1 && (y as i8 - *cy as i8).abs() <= 1
+        }))
+    }
+}
+
+//the rows from the top, `.` for an empty cell
+impl fmt::Display for MultiField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in (1..=self.size).rev() {
+            let row: String = (1..=self.size).map(|x| self.get(x, y).map_or('.', |c| c.symbol())).collect();
+            writeln!(f, "{:>2} {}", y, row)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct MultiGame {
+    field: MultiField,
+    //None when the game is over
+    to_move: Option<Colour>,
+    //None for a pass
+    moves: Vec<(Colour, Option<(u8, u8)>)>,
+}
+
+impl MultiGame {
+    pub fn new(players: u8) -> Result<MultiGame, String> {
+        MultiGame::with_size(MultiField::default_size(players), players)
+    }
+
+    pub fn with_size(size: u8, players: u8) -> Result<MultiGame, String> {
+        let mut game = MultiGame { field: MultiField::start(size, players)?, to_move: None, moves: vec![] };
+        game.pass_from(Colour(0));
+        Ok(game)
+    }
+
+    pub fn field(&self) -> &MultiField {
+        &self.field
+    }
+
+    pub fn to_move(&self) -> Option<Colour> {
+        self.to_move
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.to_move.is_none()
+    }
+
+    pub fn moves(&self) -> &[(Colour, Option<(u8, u8)>)] {
+        &self.moves
+    }
+
+    //the turn goes to the first player from `colour` on with a legal move, the others pass
+    fn pass_from(&mut self, colour: Colour) {
+        let mut passed = vec![];
+        let mut c = colour;
+        for _ in 0..self.field.players {
+            if !self.field.moves(c).is_empty() {
+                self.moves.extend(passed.into_iter().map(|p| (p, None)));
+                self.to_move = Some(c);
+                return;
+            }
+            passed.push(c);
+            c = c.following(self.field.players);
+        }
+        self.to_move = None;
+    }
+
+    //a move of the player to move, the number of the flipped discs
+    pub fn play(&mut self, x: u8, y: u8) -> Result<u8, String> {
+        let colour = self.to_move.ok_or("the game is over")?;
+        let n = self.field.play(x, y, colour)?;
+        self.moves.push((colour, Some((x, y))));
+        self.pass_from(colour.following(self.field.players));
+        Ok(n)
+    }
+
+    pub fn scores(&self) -> Vec<u8> {
+        self.field.scores()
+    }
+
+    //the players with the most discs
+    pub fn winners(&self) -> Vec<Colour> {
+        let scores = self.scores();
+        let max = scores.iter().max().copied().unwrap_or(0);
+        self.field.colours().into_iter().filter(|c| scores[c.0 as usize] == max).collect()
+    }
+}
+
+
+//a corner if there is one, then the moves which flip the most discs and do not give a corner away;
+//None if there is no move (pass)
+pub fn multi_computer_move(f: &MultiField, colour: Colour) -> Option<(u8, u8)> {
+    let moves = f.moves(colour);
+    let value = |((x, y), n): &((u8, u8), u8)| -> i32 {
+        let bonus = if f.is_corner(*x, *y) {
+            100
+        } else if f.near_empty_corner(*x, *y) {
+            -50
+        } else {
+            0
+        };
+        bonus + *n as i32
+    };
+    let best = moves.iter().map(value).max()?;
+    let best: Vec<(u8, u8)> = moves.iter().filter(|m| value(m) == best).map(|(p, _)| *p).collect();
+    let mut rng = rand::thread_rng();
+    Some(best[rng.gen_range(0..best.len())])
+}
+
+
+#[cfg(test)]
+use crate::field::{Field, FieldAction};
+#[cfg(test)]
+use crate::point::{Point, BOARD_SIZE};
+
+#[test]
+fn multi_start_test() {
+    //the two-player start is the standard one
+    let f = MultiField::start(BOARD_SIZE, 2).unwrap();
+    let mut standard = Field::new();
+    standard.init();
+    for x in 1..=BOARD_SIZE {
+        for y in 1..=BOARD_SIZE {
+            let t = standard.get_type(&Point::new(x, y));
+            assert_eq!(Colour::from_occupy(t), f.get(x, y));
+        }
+    }
+
+    let f = MultiField::start(9, 3).unwrap();
+    assert_eq!(vec![3, 3, 3], f.scores());
+    assert_eq!(Some(Colour::new(0)), f.get(4, 4));
+    assert_eq!(Some(Colour::new(2)), f.get(6, 4));
+    assert_eq!(Some(" 6 ...+#O..."), f.to_string().lines().nth(3));
+    assert!(!f.moves(Colour::new(2)).is_empty());
+
+    assert!(MultiField::new(8, 3).is_err());
+    assert!(MultiField::new(10, 5).is_err());
+    assert!(MultiField::new(18, 4).is_err());
+    assert_eq!(10, MultiGame::new(4).unwrap().field().size());
+}
+
+#[test]
+fn multi_flips_test() {
+    let mut f = MultiField::new(6, 4).unwrap();
+    let (a, b, c) = (Colour::new(0), Colour::new(1), Colour::new(2));
+    //the discs of both other colours are bracketed
+    f.set(2, 1, Some(b));
+    f.set(3, 1, Some(c));
+    f.set(4, 1, Some(a));
+    f.set(1, 2, Some(c));
+    assert_eq!(vec![((1, 1), 2)], f.moves(a));
+    assert_eq!(2, f.play(1, 1, a).unwrap());
+    assert_eq!(vec![4, 0, 1, 0], f.scores());
+    assert!(f.play(6, 6, b).is_err());
+}
+
+#[test]
+fn multi_game_test() {
+    let mut game = MultiGame::new(3).unwrap();
+    assert_eq!(Some(Colour::new(0)), game.to_move());
+    let ((x, y), _) = game.field().moves(Colour::new(0))[0];
+    game.play(x, y).unwrap();
+    assert_eq!(Some(Colour::new(1)), game.to_move());
+
+    while let Some(colour) = game.to_move() {
+        let (x, y) = multi_computer_move(game.field(), colour).unwrap();
+        game.play(x, y).unwrap();
+    }
+    assert!(game.is_over());
+    assert!(game.play(1, 1).is_err());
+    assert!(!game.winners().is_empty());
+    let played = game.moves().iter().filter(|(_, m)| m.is_some()).count();
+    assert_eq!(game.scores().iter().map(|s| *s as usize).sum::<usize>(), played + 9);
+}
+
+#[test]
+fn multi_pass_test() {
+    let mut game = MultiGame::with_size(7, 3).unwrap();
+    let (a, b, c) = (Colour::new(0), Colour::new(1), Colour::new(2));
+    let mut f = MultiField::new(7, 3).unwrap();
+    f.set(1, 1, Some(a));
+    f.set(2, 1, Some(c));
+    f.set(4, 4, Some(a));
+    f.set(5, 5, Some(c));
+    //no other disc next to it
+    f.set(6, 1, Some(b));
+    game.field = f;
+    game.pass_from(a);
+    assert_eq!(Some(a), game.to_move());
+    game.play(3, 1).unwrap();
+    //`O` has no move and passes
+    assert_eq!(Some(c), game.to_move());
+    assert_eq!((b, None), game.moves()[1]);
+    assert!(game.play(6, 6).is_err());
+    game.play(3, 3).unwrap();
+    //nobody can move, three discs each
+    assert!(game.is_over());
+    assert_eq!(vec![a, c], game.winners());
+}
+
+#[test]
+fn multi_computer_test() {
+    let mut f = MultiField::new(7, 3).unwrap();
+    let (a, b) = (Colour::new(0), Colour::new(1));
+    f.set(1, 2, Some(b));
+    f.set(1, 3, Some(a));
+    f.set(3, 3, Some(b));
+    f.set(3, 4, Some(b));
+    f.set(3, 5, Some(a));
+    //the corner before the two flips of (3,2)
+    assert_eq!(Some((1, 1)), multi_computer_move(&f, a));
+
+    let mut f = MultiField::new(7, 3).unwrap();
+    f.set(3, 3, Some(b));
+    f.set(4, 4, Some(b));
+    f.set(5, 5, Some(a));
+    f.set(4, 2, Some(b));
+    f.set(4, 3, Some(a));
+    //(2,2) flips two, but gives the corner away
+    let p = multi_computer_move(&f, a).unwrap();
+    assert_ne!((2, 2), p);
+    assert_eq!(Some(1), f.moves(a).iter().find(|(m, _)| *m == p).map(|(_, n)| *n));
+    assert!(multi_computer_move(&f, Colour::new(2)).is_none());
+}
@@ -0,0 +1,206 @@
+/*
+OBF (Othello Board Format): one position per line
+
+    ---------------------------OX------XO--------------------------- X; D3:+0; C4:+0; F5:+0; E6:+0;
+
+  * 64 cells, a1..h1 first (row 8 of the field), `X` black, `O` white, `-` empty
+    (`@` for a blocked cell is an extension of rustversi)
+  * the side to move, `X` or `O`
+  * optional `;`-separated move scores for the side to move, `PA` for a pass
+
+The older two-line form of the FFO problem sets ("<64 cells>" followed by "Black to move") is read as well.
+Lines starting with `%` are comments.
+*/
+use core::fmt;
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::Field;
+use crate::parser::{parse_board, ParseError};
+#[cfg(test)]
+use crate::field::FieldAction;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveScore {
+    //None for a pass
+    pub mv: Option<Point>,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObfRecord {
+    pub field: Field,
+    pub to_move: OccupyType,
+    pub scores: Vec<MoveScore>,
+}
+
+
+fn side_from_str(s: &str) -> Option<OccupyType> {
+    match s {
+        "X" | "x" | "#" | "*" => Some(OccupyType::Black),
+        "O" | "o" | "0" => Some(OccupyType::White),
+        _ => None,
+    }
+}
+
+fn side_symbol(bw: OccupyType) -> char {
+    if bw == OccupyType::White { 'O' } else { 'X' }
+}
+
+
+impl ObfRecord {
+    pub fn new(field: Field, to_move: OccupyType) -> Self {
+        ObfRecord { field, to_move, scores: vec![] }
+    }
+
+    //one OBF line, errors are reported for line 1
+    pub fn from_string(s: &str) -> Result<ObfRecord, ParseError> {
+        let line = s.trim();
+        let size = (BOARD_SIZE * BOARD_SIZE) as usize;
+        let board: String = line.chars().take(size).collect();
+        if board.chars().count() != size {
+            return Err(ParseError::new(1, 0, &format!("expected {} cells", size)));
+        }
+        let field = parse_board(&board)?;
+
+        let rest = &line[board.len()..];
+        let mut parts = rest.split(';');
+        let side = parts.next().unwrap_or("").trim();
+        let column = size + 1 + rest.find(|c: char| !c.is_whitespace()).unwrap_or(0);
+        let to_move = side_from_str(side)
+            .ok_or_else(|| ParseError::new(1, column, &format!("wrong side to move '{}'", side)))?;
+
+        let mut scores = vec![];
+        for part in parts {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let column = size + rest.find(part).unwrap_or(0) + 1;
+            let err = || ParseError::new(1, column, &format!("wrong move score '{}'", part));
+            let (mv, score) = match part.find(':') {
+                Some(i) => (&part[..i], &part[i + 1..]),
+                None => return Err(err()),
+            };
+            let mv = match mv.trim() {
+                "PA" | "pa" | "--" => None,
+                m => Some(Point::from_algebraic(m).ok_or_else(err)?),
+            };
+            let score = score.trim().parse::<i32>().map_err(|_| err())?;
+            scores.push(MoveScore { mv, score });
+        }
+        Ok(ObfRecord { field, to_move, scores })
+    }
+}
+
+impl fmt::Display for ObfRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in (1..=BOARD_SIZE).rev() {
+            for x in 1..=BOARD_SIZE {
+                let c = match self.field.get_type(&Point::new(x, y)) {
+                    OccupyType::Empty => '-',
+                    OccupyType::Blocked => '@',
+                    bw => side_symbol(bw),
+                };
+                write!(f, "{}", c)?;
+            }
+        }
+        write!(f, " {};", side_symbol(self.to_move))?;
+        for s in &self.scores {
+            match s.mv {
+                Some(p) => write!(f, " {}:{:+};", p.to_algebraic().to_uppercase(), s.score)?,
+                None => write!(f, " PA:{:+};", s.score)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+
+//all positions of an .obf file (or of the two-line FFO form)
+pub fn read_all(s: &str) -> Result<Vec<ObfRecord>, ParseError> {
+    let mut ret = vec![];
+    let mut lines = s.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('%'))
+        .peekable();
+    while let Some((n, line)) = lines.next() {
+        let with_line = |mut e: ParseError| {
+            e.line = n;
+            e
+        };
+        //"Black to move" on the next line
+        let next_side = lines.peek().and_then(|(_, next)| {
+            let next = next.to_lowercase();
+            if next.starts_with("black") {
+                Some('X')
+            } else if next.starts_with("white") {
+                Some('O')
+            } else {
+                None
+            }
+        });
+        let record = match next_side {
+            Some(side) if !line.contains(';') => {
+                lines.next();
+                let board: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+                ObfRecord::from_string(&format!("{} {};", board, side)).map_err(with_line)?
+            }
+            _ => ObfRecord::from_string(line).map_err(with_line)?,
+        };
+        ret.push(record);
+    }
+    Ok(ret)
+}
+
+pub fn write_all(records: &[ObfRecord]) -> String {
+    records.iter().map(|r| format!("{}\n", r)).collect()
+}
+
+
+#[test]
+fn obf_read_test() {
+    let r = ObfRecord::from_string("---------------------------OX------XO--------------------------- X; D3:+0; C4:-2; PA:+5;").unwrap();
+    let mut f = Field::new();
+    f.init();
+    assert_eq!(f.serialize(), r.field.serialize());
+    assert_eq!(OccupyType::Black, r.to_move);
+    assert_eq!(MoveScore { mv: Some(Point::new(4, 6)), score: 0 }, r.scores[0]);
+    assert_eq!(MoveScore { mv: Some(Point::new(3, 5)), score: -2 }, r.scores[1]);
+    assert_eq!(MoveScore { mv: None, score: 5 }, r.scores[2]);
+    //black really can move to d3
+    assert!(r.field.is_valid_move(&r.scores[0].mv.unwrap(), r.to_move).0);
+}
+
+#[test]
+fn obf_write_test() {
+    let mut f = Field::new();
+    f.init();
+    let mut r = ObfRecord::new(f, OccupyType::White);
+    assert_eq!("---------------------------OX------XO--------------------------- O;", r.to_string());
+    r.scores.push(MoveScore { mv: Some(Point::new(6, 4)), score: 12 });
+    let s = r.to_string();
+    assert!(s.ends_with(" O; F5:+12;"));
+    let back = ObfRecord::from_string(&s).unwrap();
+    assert_eq!(r.scores, back.scores);
+}
+
+#[test]
+fn obf_read_all_test() {
+    let text = "% two positions
+---------------------------OX------XO--------------------------- X;
+
+--XXXXX--OOOXX-O-OOOXXOX-OXOXOXXOXXXOXXX--XOXOXX-XXXOOO--OOOOO--
+Black to move
+";
+    let records = read_all(text).unwrap();
+    assert_eq!(2, records.len());
+    assert_eq!(OccupyType::Black, records[1].to_move);
+    assert_eq!(OccupyType::Empty, records[1].field.get_type(&Point::new(1, 8)));
+    assert_eq!(OccupyType::Black, records[1].field.get_type(&Point::new(3, 8)));
+
+    let err = read_all("\n---------------------------OX------XO--------------------------- Z;").unwrap_err();
+    assert_eq!(2, err.line);
+    assert_eq!(66, err.column);
+}
@@ -1,10 +1,14 @@
 use core::fmt;
 
+//serialized as "black", "white", "empty" or "blocked"
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum OccupyType {
     Black,
     White,
     Empty,
+    //a hole: no disc may be placed there and it stops the flipping lines
+    Blocked,
 }
 
 impl OccupyType {
@@ -13,6 +17,7 @@ impl OccupyType {
             OccupyType::Black => OccupyType::White,
             OccupyType::White => OccupyType::Black,
             OccupyType::Empty => { panic!("No opposite for Empty type");}
+            OccupyType::Blocked => { panic!("No opposite for Blocked type");}
         };
     }
 
@@ -21,16 +26,26 @@ impl OccupyType {
         return match (*s).as_str() {
             "O" => OccupyType::White,
             "#" => OccupyType::Black,
+            "@" => OccupyType::Blocked,
             _ => OccupyType::Empty
         };
     }
 
+    //a side to move: only "#" and "O", the other symbols are no player
+    pub fn side_from_string(s: &str) -> Option<OccupyType> {
+        match OccupyType::from_string(&s.to_string()) {
+            bw @ (OccupyType::Black | OccupyType::White) => Some(bw),
+            _ => None,
+        }
+    }
+
 
     pub fn to_string(&self) -> String {
         return match self {
             OccupyType::Black => { "#".to_string() }
             OccupyType::White => { "O".to_string() }
             OccupyType::Empty => { " ".to_string() }
+            OccupyType::Blocked => { "@".to_string() }
         };
     }
 }
@@ -49,7 +64,24 @@ fn occupy_print_test() {
     assert_eq!("#".to_string(), format!("{}", p));
 }
 
+#[test]
+fn occupy_from_string_test() {
+    for t in [OccupyType::Black, OccupyType::White, OccupyType::Empty, OccupyType::Blocked] {
+        assert_eq!(t, OccupyType::from_string(&t.to_string()));
+    }
+    assert_eq!(Some(OccupyType::Black), OccupyType::side_from_string("#"));
+    assert_eq!(None, OccupyType::side_from_string("@"));
+    assert_eq!(None, OccupyType::side_from_string(" "));
+}
+
 #[test]
 fn occupy_opposite_test() {
     assert_eq!("O".to_string(), OccupyType::get_opposite_type(OccupyType::Black).to_string());
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn occupy_serde_test() {
+    assert_eq!(r#""black""#, serde_json::to_string(&OccupyType::Black).unwrap());
+    assert_eq!(OccupyType::White, serde_json::from_str::<OccupyType>(r#""white""#).unwrap());
+}
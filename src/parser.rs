@@ -0,0 +1,337 @@
+/*
+Board parser for the common text notations. The first row of the text is row 8 (the top of `Field::to_string`).
+
+  black: `#` `X` `x` `*` `B` `●`
+  white: `O` `o` `0` `W` `○`
+  empty: `-` `.` `_` `·` and space
+  blocked (a hole): `@` `■`
+
+Accepted layouts:
+  * one line of 64 cells, e.g. the `FieldAction::serialize` string or "---------------------------OX------XO---------------------------"
+  * 8 lines of 8 cells, cells may be separated by spaces: "- - X O - - - -"
+  * the same with row numbers on the left and/or right, column headers ("a b c ..." or "1 2 3 ...")
+    and separator lines, so the output of `Field::to_string` is accepted as well
+  * a box-drawing frame around the grid (the unicode style of `render`)
+
+Blank lines are skipped, so in multi-line grids an empty cell must be written explicitly.
+*/
+use core::fmt;
+use std::str::FromStr;
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::Field;
+#[cfg(test)]
+use crate::field::FieldAction;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    //1-based, 0 if the error is not bound to a line or column
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: &str) -> Self {
+        ParseError { line, column, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line, self.column) {
+            (0, _) => write!(f, "{}", self.message),
+            (line, 0) => write!(f, "line {}: {}", line, self.message),
+            (line, column) => write!(f, "line {}, column {}: {}", line, column, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+pub fn cell_from_char(c: char) -> Option<OccupyType> {
+    match c {
+        '#' | 'X' | 'x' | '*' | 'B' | '●' => Some(OccupyType::Black),
+        'O' | 'o' | '0' | 'W' | '○' => Some(OccupyType::White),
+        '-' | '.' | '_' | '·' | ' ' => Some(OccupyType::Empty),
+        '@' | '■' => Some(OccupyType::Blocked),
+        _ => None,
+    }
+}
+
+
+//cells of one row, with 1-based columns for the error messages
+type Cells = Vec<(usize, char)>;
+
+fn is_header(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace() && *c != '|').collect();
+    let columns: String = (0..BOARD_SIZE).map(|i| (b'a' + i) as char).collect();
+    let numbers: String = (1..=BOARD_SIZE).map(|i| i.to_string()).collect();
+    compact == columns || compact == columns.to_uppercase() || compact == numbers
+}
+
+const BOX_CHARS: &str = "─│┌┐└┘├┤┬┴┼";
+
+//"| - |" or "|   |" segments, one symbol or a blank cell: a grid row, even when all its cells are empty
+fn has_cells(line: &str) -> bool {
+    let parts: Vec<&str> = line.split('|').collect();
+    parts.len() > 2 && parts[1..parts.len() - 1].iter().any(|part| part.trim().chars().count() == 1 || (!part.is_empty() && part.trim().is_empty()))
+}
+
+fn is_separator(line: &str) -> bool {
+    let trimmed = line.trim();
+    !has_cells(trimmed) && trimmed.chars().all(|c| c == '-' || c == '+' || c == '|' || c == '=' || c == ' ' || BOX_CHARS.contains(c))
+        && (trimmed.contains(['+', '|', '='])
+            || trimmed.contains(|c| BOX_CHARS.contains(c))
+            || trimmed.chars().filter(|c| *c == '-').count() > BOARD_SIZE as usize)
+}
+
+fn is_label(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) && !s.starts_with('0')
+}
+
+//"| O | # |   |" grid rows
+fn split_grid_row(n: usize, line: &str) -> Result<Cells, ParseError> {
+    let mut cells = vec![];
+    let mut column = 1;
+    let parts: Vec<&str> = line.split('|').collect();
+    for (i, part) in parts.iter().enumerate() {
+        let trimmed = part.trim();
+        let edge = i == 0 || i == parts.len() - 1;
+        if !(edge && (trimmed.is_empty() || is_label(trimmed))) {
+            let offset = part.chars().take_while(|c| c.is_whitespace()).count();
+            if trimmed.chars().count() > 1 {
+                return Err(ParseError::new(n, column + offset, "expected one symbol in the cell"));
+            }
+            cells.push((column + offset, trimmed.chars().next().unwrap_or(' ')));
+        }
+        column += part.chars().count() + 1;
+    }
+    Ok(cells)
+}
+
+fn split_row(line: &str) -> Cells {
+    let chars: Cells = line.chars().enumerate().map(|(i, c)| (i + 1, c)).collect();
+    let compact = |cells: &[(usize, char)]| -> Cells {
+        cells.iter().filter(|(_, c)| !c.is_whitespace()).cloned().collect()
+    };
+    if chars.len() == BOARD_SIZE as usize {
+        return chars;
+    }
+    let all = compact(&chars);
+    if all.len() == BOARD_SIZE as usize {
+        return all;
+    }
+
+    //row numbers on either side
+    let mut tokens = line.split_whitespace();
+    let first = tokens.next().unwrap_or("");
+    let last = tokens.last().unwrap_or("");
+    let mut start = 0;
+    let mut end = line.len();
+    if is_label(first) {
+        start = line.find(first).unwrap() + first.len();
+    }
+    if is_label(last) && line.trim_end().len() - last.len() >= start {
+        end = line.trim_end().len() - last.len();
+    }
+    //labels are ascii so byte and char positions match up to them
+    let start = line[..start].chars().count();
+    let end = line[..end].chars().count();
+    let inner = &chars[start..end];
+    let trimmed: Cells = {
+        let from = inner.iter().position(|(_, c)| !c.is_whitespace()).unwrap_or(inner.len());
+        let to = inner.iter().rposition(|(_, c)| !c.is_whitespace()).map(|i| i + 1).unwrap_or(from);
+        inner[from..to].to_vec()
+    };
+    if trimmed.len() == BOARD_SIZE as usize {
+        return trimmed;
+    }
+    compact(inner)
+}
+
+fn check_row(n: usize, cells: &[(usize, char)]) -> Result<(), ParseError> {
+    if cells.len() != BOARD_SIZE as usize {
+        let column = cells.first().map(|(c, _)| *c).unwrap_or(0);
+        return Err(ParseError::new(n, column, &format!("expected {} cells, found {}", BOARD_SIZE, cells.len())));
+    }
+    match cells.iter().find(|(_, c)| cell_from_char(*c).is_none()) {
+        Some((column, c)) => Err(ParseError::new(n, *column, &format!("unexpected symbol '{}'", c))),
+        None => Ok(()),
+    }
+}
+
+//rows are checked already
+fn to_field(rows: &[Cells]) -> Field {
+    let mut f = Field::new();
+    for (i, cells) in rows.iter().enumerate() {
+        let y = BOARD_SIZE - i as u8;
+        for (x, (_, c)) in cells.iter().enumerate() {
+            let bw = cell_from_char(*c).unwrap();
+            if bw != OccupyType::Empty {
+                f.set_type(&Point::new(x as u8 + 1, y), bw);
+            }
+        }
+    }
+    f
+}
+
+
+//the whole board in one line
+fn parse_line(n: usize, line: &str) -> Result<Field, ParseError> {
+    let size = (BOARD_SIZE * BOARD_SIZE) as usize;
+    let mut cells: Cells = line.chars().enumerate().map(|(i, c)| (i + 1, c)).collect();
+    if cells.len() != size {
+        cells.retain(|(_, c)| !c.is_whitespace());
+    }
+    if cells.len() != size {
+        return Err(ParseError::new(n, 0, &format!("expected {} cells, found {}", size, cells.len())));
+    }
+    let rows: Vec<Cells> = cells.chunks(BOARD_SIZE as usize).map(|row| row.to_vec()).collect();
+    for row in &rows {
+        check_row(n, row)?;
+    }
+    Ok(to_field(&rows))
+}
+
+pub fn parse_board(s: &str) -> Result<Field, ParseError> {
+    let size = (BOARD_SIZE * BOARD_SIZE) as usize;
+    //the `serialize` string of an empty board is 64 spaces, a blank line
+    let raw: Vec<(usize, &str)> = s.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    if let [(n, line)] = raw[..] {
+        if line.chars().count() == size {
+            return parse_line(n, line);
+        }
+    }
+    //the vertical box-drawing lines are only a frame
+    let lines: Vec<(usize, String)> = s.lines().enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r').replace('│', " ")))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return Err(ParseError::new(0, 0, "the board is empty"));
+    }
+
+    if lines.len() == 1 {
+        return parse_line(lines[0].0, &lines[0].1);
+    }
+
+    let mut rows: Vec<Cells> = vec![];
+    let mut last = 0;
+    for (n, line) in &lines {
+        let (n, line) = (*n, line.as_str());
+        if is_header(line) || is_separator(line) {
+            continue;
+        }
+        if rows.len() == BOARD_SIZE as usize {
+            return Err(ParseError::new(n, 0, &format!("more than {} rows", BOARD_SIZE)));
+        }
+        let cells = if line.contains('|') { split_grid_row(n, line)? } else { split_row(line) };
+        check_row(n, &cells)?;
+        rows.push(cells);
+        last = n;
+    }
+    if rows.len() != BOARD_SIZE as usize {
+        return Err(ParseError::new(last, 0, &format!("expected {} rows, found {}", BOARD_SIZE, rows.len())));
+    }
+    Ok(to_field(&rows))
+}
+
+
+impl FromStr for Field {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_board(s)
+    }
+}
+
+
+#[test]
+fn parser_one_line_test() {
+    let f = parse_board("---------------------------OX------XO---------------------------").unwrap();
+    let (w, b) = f.get_score_wb();
+    assert_eq!((2, 2), (w, b));
+    assert_eq!(OccupyType::White, f.get_type(&Point::new(4, 5)));
+
+    let mut g = Field::new();
+    g.init();
+    let s = g.serialize();
+    assert_eq!(s, parse_board(&s).unwrap().serialize());
+
+    //an empty board is 64 spaces
+    let empty = Field::new().serialize();
+    assert_eq!(empty, parse_board(&empty).unwrap().serialize());
+    let mut h = Field::new();
+    h.deserialize(&empty);
+    assert_eq!((0, 0), h.get_score_wb());
+}
+
+#[test]
+fn parser_grid_test() {
+    let f: Field = "
+  a b c d e f g h
+1 - - - - - - - - 1
+2 . . . . . . . . 2
+3 - - - - ● - - - 3
+4 - - - O X - - - 4
+5 - - - x o - - - 5
+6 - - - - - - - - 6
+7 - - - - - - - - 7
+8 - - - - - - - - 8
+  a b c d e f g h
+".parse().unwrap();
+    assert_eq!(OccupyType::Black, f.get_type(&Point::new(5, 6)));
+    assert_eq!(OccupyType::White, f.get_type(&Point::new(4, 5)));
+    assert_eq!((2, 3), f.get_score_wb());
+
+    //a framed grid with a row of empty cells
+    let framed: Field = "
++---+---+---+---+---+---+---+---+
+| - | - | - | - | - | - | - | - |
+| - | - | - | - | - | - | - | - |
+| - | - | - | - | - | - | - | - |
+| - | - | - | O | # | - | - | - |
+| - | - | - | # | O | - | - | - |
+| - | - | - | - | - | - | - | - |
+| - | - | - | - | - | - | - | - |
+|   |   |   |   |   |   |   |   |
++---+---+---+---+---+---+---+---+
+".parse().unwrap();
+    assert_eq!(OccupyType::White, framed.get_type(&Point::new(4, 5)));
+    assert_eq!((2, 2), framed.get_score_wb());
+
+    let compact: Field = "........\n........\n........\n...O*...\n...*O...\n........\n........\n........".parse().unwrap();
+    assert_eq!((2, 2), compact.get_score_wb());
+}
+
+#[test]
+fn parser_to_string_test() {
+    let mut f = Field::new();
+    f.init();
+    f.move_in_game(&Point::new(5, 6), OccupyType::White);
+    let g = parse_board(&f.to_string()).unwrap();
+    assert_eq!(f.serialize(), g.serialize());
+}
+
+#[test]
+fn parser_errors_test() {
+    let err = parse_board("--------\n--------\n---?----\n").unwrap_err();
+    assert_eq!(ParseError::new(3, 4, "unexpected symbol '?'"), err);
+    assert_eq!("line 3, column 4: unexpected symbol '?'", err.to_string());
+
+    let err = parse_board("--------\n--------\n").unwrap_err();
+    assert_eq!("line 2: expected 8 rows, found 2", err.to_string());
+
+    let err = parse_board("--------\n-------\n").unwrap_err();
+    assert_eq!(2, err.line);
+
+    assert!(parse_board("#O").is_err());
+    assert!(parse_board("").is_err());
+}
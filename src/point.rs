@@ -7,12 +7,34 @@ pub const C18: Point = Point{x:1, y:BOARD_SIZE};
 pub const C81: Point = Point{x:BOARD_SIZE, y:1};
 pub const C88: Point = Point{x:BOARD_SIZE, y:BOARD_SIZE};
 
+//serialized as {"x": 4, "y": 3}
 #[derive(Debug, Hash, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "PointData"))]
 pub struct Point {
     x: u8,
     y: u8,
 }
 
+//unchecked point, Point::new panics
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PointData {
+    x: u8,
+    y: u8,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<PointData> for Point {
+    type Error = String;
+
+    fn try_from(p: PointData) -> Result<Self, Self::Error> {
+        if !Point::check_point(p.x as i8, p.y as i8) {
+            return Err(format!("point ({},{}) is out of the board", p.x, p.y));
+        }
+        Ok(Point::new(p.x, p.y))
+    }
+}
+
 impl Point {
     pub fn check_point(x: i8, y: i8) -> bool {
         return x >= 1 && x <= (BOARD_SIZE as i8) && y >=1  && y <= (BOARD_SIZE as i8);
@@ -21,6 +43,38 @@ impl Point {
         format!("({},{})", self.x, self.y)
     }
 
+    //accepts "x,y", "(x,y)" and "x y"
+    pub fn from_string(s: &str) -> Option<Point> {
+        let s = s.trim().trim_start_matches('(').trim_end_matches(')');
+        let mut iter = s.split(|c: char| c == ',' || c.is_ascii_whitespace()).filter(|v| !v.is_empty());
+        let x = iter.next()?.parse::<i8>().ok()?;
+        let y = iter.next()?.parse::<i8>().ok()?;
+        if iter.next().is_some() || !Point::check_point(x, y) {
+            return None;
+        }
+        Some(Point::new(x as u8, y as u8))
+    }
+
+    //"a1" is the top left corner (1,8) as in the Othello notation
+    pub fn to_algebraic(&self) -> String {
+        format!("{}{}", (b'a' + self.x - 1) as char, BOARD_SIZE + 1 - self.y)
+    }
+
+    pub fn from_algebraic(s: &str) -> Option<Point> {
+        let mut chars = s.trim().chars();
+        let column = chars.next()?.to_ascii_lowercase();
+        if !column.is_ascii_lowercase() {
+            return None;
+        }
+        let row = chars.as_str().parse::<i8>().ok()?;
+        let x = (column as u8 - b'a' + 1) as i8;
+        let y = BOARD_SIZE as i8 + 1 - row;
+        if !Point::check_point(x, y) {
+            return None;
+        }
+        Some(Point::new(x as u8, y as u8))
+    }
+
     pub fn new(x: u8, y: u8) -> Self {
         //println!("{:?}{:?}",x,y);
         if x <= 0 || x > BOARD_SIZE ||
@@ -58,6 +112,21 @@ impl fmt::Display for Point {
     }
 }
 
+//a move in the algebraic notation, `f5` or `pass`
+pub fn move_to_string(mv: Option<Point>) -> String {
+    match mv {
+        Some(p) => p.to_algebraic(),
+        None => "pass".to_string(),
+    }
+}
+
+pub fn move_from_string(s: &str) -> Option<Option<Point>> {
+    match s {
+        "pass" => Some(None),
+        _ => Point::from_algebraic(s).map(Some),
+    }
+}
+
 #[test]
 fn point_test() {
     let p = Point::new(4,2);
@@ -65,6 +134,39 @@ fn point_test() {
     assert_eq!("(4,2)", format!("{}", p));
     assert_eq!(2, p.y());
 }
+#[test]
+fn point_from_string_test() {
+    assert_eq!(Some(Point::new(4, 2)), Point::from_string("4,2"));
+    assert_eq!(Some(Point::new(4, 2)), Point::from_string("(4,2)"));
+    assert_eq!(Some(Point::new(4, 2)), Point::from_string(" 4 2 "));
+    assert_eq!(None, Point::from_string("0,2"));
+    assert_eq!(None, Point::from_string("4,2,1"));
+    assert_eq!(None, Point::from_string("x"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn point_serde_test() {
+    let p = Point::new(4, 2);
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(r#"{"x":4,"y":2}"#, json);
+    assert_eq!(p, serde_json::from_str::<Point>(&json).unwrap());
+    assert!(serde_json::from_str::<Point>(r#"{"x":9,"y":2}"#).is_err());
+}
+
+#[test]
+fn point_algebraic_test() {
+    assert_eq!("a1", C18.to_algebraic());
+    assert_eq!("h8", C81.to_algebraic());
+    assert_eq!("f5", Point::new(6, 4).to_algebraic());
+    assert_eq!(Some(Point::new(6, 4)), Point::from_algebraic("F5"));
+    assert_eq!(None, Point::from_algebraic("i1"));
+    assert_eq!(None, Point::from_algebraic("a9"));
+    assert_eq!(None, Point::from_algebraic("5f"));
+    assert_eq!(Some(None), move_from_string("pass"));
+    assert_eq!("f5", move_to_string(move_from_string("f5").unwrap()));
+}
+
 #[test]
 fn point_eq_test() {
     let p1 = Point::new(4,2);
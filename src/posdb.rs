@@ -0,0 +1,350 @@
+/*
+Position database: positions collected from games with the side to move and the moves which reached them.
+Persisted as a text file, one position per line after the header:
+
+    rustversi-posdb: 1
+    ---------------------------OX------XO--------------------------- X 12
+    -------------------X-------XX------XO--------------------------- O 7 f5
+
+  the 64 cells (`X`, `O`, `-`, a1..h1 first as in OBF), the side to move, how many times the position
+  was added and the moves from the standard position (algebraic, `pa` for a pass; none for a position
+  added without its game).
+
+A position is stored once per side to move, with the moves of its first occurrence.
+Lines starting with `;` are ignored.
+*/
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::RangeInclusive;
+
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::obf::ObfRecord;
+use crate::game::possible_movement;
+
+
+pub const POSDB_VERSION: u32 = 1;
+const HEADER: &str = "rustversi-posdb";
+
+
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub field: Field,
+    pub to_move: OccupyType,
+    //None for a pass, empty if the game is not known
+    pub moves: Vec<Option<Point>>,
+    pub count: u32,
+}
+
+//all the conditions must hold, None (or empty) matches anything
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    //the cells which must be black, white or empty
+    pub cells: Vec<(Point, OccupyType)>,
+    pub discs: Option<RangeInclusive<u8>>,
+    pub black: Option<RangeInclusive<u8>>,
+    pub white: Option<RangeInclusive<u8>>,
+    pub to_move: Option<OccupyType>,
+    //the moves of the position start with these ones, "reachable from the opening"
+    pub moves: Vec<Option<Point>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PositionDb {
+    positions: Vec<Position>,
+    //(black, white, to move) -> index
+    exact: HashMap<(u64, u64, bool), usize>,
+    //the canonical hash of `symmetry` -> indices
+    canonical: HashMap<u64, Vec<usize>>,
+    //disc count -> indices
+    discs: BTreeMap<u8, Vec<usize>>,
+}
+
+
+fn exact_key(field: &Field, to_move: OccupyType) -> (u64, u64, bool) {
+    let (black, white) = field.bitboards();
+    (black, white, to_move == OccupyType::White)
+}
+
+fn disc_count(field: &Field) -> u8 {
+    let (w, b) = field.get_score_wb();
+    w + b
+}
+
+fn move_to_string(m: &Option<Point>) -> String {
+    match m {
+        Some(p) => p.to_algebraic(),
+        None => "pa".to_string(),
+    }
+}
+
+//algebraic "f5", "x,y" or a pass
+pub fn parse_move(s: &str) -> Option<Option<Point>> {
+    match s.to_lowercase().as_str() {
+        "pa" | "pass" | "--" => Some(None),
+        m => Point::from_algebraic(m).or_else(|| Point::from_string(m)).map(Some),
+    }
+}
+
+fn in_range(range: &Option<RangeInclusive<u8>>, n: u8) -> bool {
+    range.as_ref().map_or(true, |r| r.contains(&n))
+}
+
+
+impl Query {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn matches(&self, position: &Position) -> bool {
+        let (w, b) = position.field.get_score_wb();
+        self.cells.iter().all(|(p, bw)| position.field.get_type(p) == *bw)
+            && in_range(&self.discs, w + b)
+            && in_range(&self.black, b)
+            && in_range(&self.white, w)
+            && self.to_move.map_or(true, |bw| bw == position.to_move)
+            && position.moves.starts_with(&self.moves)
+    }
+}
+
+
+impl PositionDb {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    //true if the position is new
+    pub fn add(&mut self, field: &Field, to_move: OccupyType, moves: &[Option<Point>]) -> bool {
+        self.insert(Position { field: field.clone(), to_move, moves: moves.to_vec(), count: 1 })
+    }
+
+    fn insert(&mut self, position: Position) -> bool {
+        let key = exact_key(&position.field, position.to_move);
+        if let Some(&i) = self.exact.get(&key) {
+            let known = &mut self.positions[i];
+            known.count += position.count;
+            if known.moves.is_empty() {
+                known.moves = position.moves;
+            }
+            return false;
+        }
+        let i = self.positions.len();
+        self.exact.insert(key, i);
+        self.canonical.entry(position.field.canonical_hash()).or_default().push(i);
+        self.discs.entry(disc_count(&position.field)).or_default().push(i);
+        self.positions.push(position);
+        true
+    }
+
+    //replays the game from the standard position and adds every position of it, returns the number of new ones;
+    //a side without a legal move passes even if the pass is not in the moves (as in WTHOR)
+    pub fn add_game(&mut self, moves: &[Option<Point>], first: OccupyType) -> Result<usize, String> {
+        let mut f = Field::new();
+        f.init();
+        self.add_game_from(&f, first, moves)
+    }
+
+    //the same from any position, the moves are kept only for the standard one
+    pub fn add_game_from(&mut self, start: &Field, first: OccupyType, moves: &[Option<Point>]) -> Result<usize, String> {
+        let mut standard = Field::new();
+        standard.init();
+        let keep = start.bitboards() == standard.bitboards();
+
+        let mut f = start.clone();
+        let mut bw = first;
+        let mut played = vec![];
+        let mut added = self.add(&f, bw, &[]) as usize;
+        for (i, m) in moves.iter().enumerate() {
+            let legal = possible_movement(bw, &f);
+            match m {
+                Some(_) if !legal => {
+                    played.push(None);
+                    bw = OccupyType::get_opposite_type(bw);
+                    added += self.add(&f, bw, if keep { &played } else { &[] }) as usize;
+                }
+                None if legal => return Err(format!("move {}: {} cannot pass", i + 1, bw)),
+                _ => {}
+            }
+            if let Some(p) = m {
+                if !f.is_valid_move(p, bw).0 {
+                    return Err(format!("move {} {} is not valid for {}", i + 1, p.to_algebraic(), bw));
+                }
+                f.move_in_game(p, bw);
+            }
+            played.push(*m);
+            bw = OccupyType::get_opposite_type(bw);
+            added += self.add(&f, bw, if keep { &played } else { &[] }) as usize;
+        }
+        Ok(added)
+    }
+
+    pub fn get(&self, field: &Field, to_move: OccupyType) -> Option<&Position> {
+        self.exact.get(&exact_key(field, to_move)).map(|&i| &self.positions[i])
+    }
+
+    //the positions equal to the field up to a symmetry
+    pub fn find_equivalent(&self, field: &Field) -> Vec<&Position> {
+        let (canonical, _) = field.canonical();
+        let key = canonical.bitboards();
+        self.canonical.get(&field.canonical_hash()).into_iter().flatten()
+            .map(|&i| &self.positions[i])
+            .filter(|p| p.field.canonical().0.bitboards() == key)
+            .collect()
+    }
+
+    pub fn query(&self, q: &Query) -> Vec<&Position> {
+        let mut candidates: Vec<usize> = match &q.discs {
+            //`BTreeMap::range` panics on a reversed range
+            Some(range) if range.is_empty() => return vec![],
+            Some(range) => self.discs.range(range.clone()).flat_map(|(_, v)| v.iter().cloned()).collect(),
+            None => (0..self.positions.len()).collect(),
+        };
+        //in the order of the file
+        candidates.sort_unstable();
+        candidates.into_iter()
+            .map(|i| &self.positions[i])
+            .filter(|p| q.matches(p))
+            .collect()
+    }
+
+    pub fn from_string(s: &str) -> Result<PositionDb, String> {
+        let mut lines = s.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'));
+        match lines.next() {
+            Some((_, line)) if line.starts_with(HEADER) => {
+                let version = line[HEADER.len()..].trim_start_matches(':').trim();
+                if version != POSDB_VERSION.to_string() {
+                    return Err(format!("unsupported database version '{}'", version));
+                }
+            }
+            _ => return Err("not a rustversi position database".to_string()),
+        }
+
+        let mut db = PositionDb::new();
+        for (n, line) in lines {
+            let err = |msg: String| format!("line {}: {}", n, msg);
+            let mut words = line.split_whitespace();
+            let (board, side, count) = match (words.next(), words.next(), words.next()) {
+                (Some(board), Some(side), Some(count)) => (board, side, count),
+                _ => return Err(err("expected `<board> <side> <count> [moves]`".to_string())),
+            };
+            let record = ObfRecord::from_string(&format!("{} {};", board, side)).map_err(|e| err(e.message))?;
+            let count = count.parse::<u32>().map_err(|_| err(format!("wrong count '{}'", count)))?;
+            let moves = words.map(|m| parse_move(m).ok_or_else(|| err(format!("wrong move '{}'", m))))
+                .collect::<Result<Vec<_>, _>>()?;
+            db.insert(Position { field: record.field, to_move: record.to_move, moves, count });
+        }
+        Ok(db)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    //an empty database if the file does not exist
+    pub fn load(path: &str) -> Result<PositionDb, String> {
+        match fs::read_to_string(path) {
+            Ok(s) => PositionDb::from_string(&s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PositionDb::new()),
+            Err(e) => Err(format!("cannot read {}: {}", path, e)),
+        }
+    }
+}
+
+//a line of the file: `<board> <side> <count> <moves>`
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut board = format!("{}", ObfRecord::new(self.field.clone(), self.to_move));
+        board.pop();
+        write!(f, "{} {}", board, self.count)?;
+        for m in &self.moves {
+            write!(f, " {}", move_to_string(m))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PositionDb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}: {}", HEADER, POSDB_VERSION)?;
+        for p in &self.positions {
+            writeln!(f, "{}", p)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+fn test_db() -> PositionDb {
+    let mut db = PositionDb::new();
+    let game = |s: &str| s.split_whitespace().map(|m| parse_move(m).unwrap()).collect::<Vec<_>>();
+    assert_eq!(6, db.add_game(&game("f5 d6 c3 d3 c4"), OccupyType::Black).unwrap());
+    //the start and f5 are known already
+    assert_eq!(2, db.add_game(&game("f5 f6 e6"), OccupyType::Black).unwrap());
+    db
+}
+
+#[test]
+fn posdb_query_test() {
+    let db = test_db();
+    assert_eq!(8, db.len());
+    let mut start = Field::new();
+    start.init();
+    assert_eq!(2, db.get(&start, OccupyType::Black).unwrap().count);
+
+    let mut q = Query::new();
+    q.moves = vec![parse_move("f5").unwrap(), parse_move("f6").unwrap()];
+    assert_eq!(2, db.query(&q).len());
+
+    let mut q = Query::new();
+    q.discs = Some(0..=7);
+    q.to_move = Some(OccupyType::White);
+    let found = db.query(&q);
+    assert_eq!(3, found.len());
+    assert_eq!(vec![parse_move("f5").unwrap()], found[0].moves);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 20..=10;
+    q.discs = Some(reversed);
+    assert!(db.query(&q).is_empty());
+
+    //d6 is white after f5 d6
+    let mut q = Query::new();
+    q.cells = vec![(Point::from_algebraic("d6").unwrap(), OccupyType::White)];
+    q.black = Some(3..=3);
+    assert_eq!(1, db.query(&q).len());
+
+    //f5 and its mirror d3 are the same position
+    let mut d3 = start.clone();
+    d3.move_in_game(&Point::from_algebraic("d3").unwrap(), OccupyType::Black);
+    assert_eq!(1, db.find_equivalent(&d3).len());
+    assert!(db.get(&d3, OccupyType::White).is_none());
+}
+
+#[test]
+fn posdb_file_test() {
+    let db = test_db();
+    let s = db.to_string();
+    assert!(s.starts_with("rustversi-posdb: 1\n---------------------------OX------XO--------------------------- X 2\n"));
+    assert!(s.contains(" O 1 f5 d6 c3\n"));
+    let loaded = PositionDb::from_string(&s).unwrap();
+    assert_eq!(s, loaded.to_string());
+
+    assert!(PositionDb::from_string("rustversi-posdb: 1\n---- X 1\n").unwrap_err().starts_with("line 2:"));
+    assert!(PositionDb::from_string("rustversi-posdb: 9\n").is_err());
+}
@@ -0,0 +1,217 @@
+/*
+The text engine protocol: a controller (`arena`) plays games between engines running as subprocesses,
+talking over their stdin and stdout, a line for every command and a line for every answer.
+
+  controller          engine
+  rvp                 rvp <name>              the handshake, the name of the engine ("rustversi search:4")
+  position <obf>      ok                      a new game from the position and the side to move, an OBF line
+                                              (`obf`, e.g. `---...OX...--- X`)
+  play <move>         ok                      the move of the side to move, `f5` or `pass`; the controller sends
+                                              every move of the game to both engines, passes included
+  go <ms> [misere]    move <move>             the move of the engine for the side to move, thinking at most ms
+                                              milliseconds; the engine does not play it until `play`; with
+                                              `misere` it plays for the fewest discs
+  quit                                        the engine exits
+
+A command the engine cannot do is answered with `error <message>`. `rustversi engine` speaks the protocol.
+*/
+use core::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::point::{Point, move_from_string, move_to_string};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::engine::{Engine, Goal};
+use crate::obf::ObfRecord;
+use crate::game::possible_movement;
+
+
+pub const HANDSHAKE: &str = "rvp";
+
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Hello,
+    Position(Field, OccupyType),
+    //None for a pass
+    Play(Option<Point>),
+    //the time and what to play for
+    Go(u64, Goal),
+    Quit,
+}
+
+impl Command {
+    pub fn from_string(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (name, arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = arg.trim();
+        let command = match name {
+            HANDSHAKE => Command::Hello,
+            "position" => {
+                let r = ObfRecord::from_string(arg).map_err(|e| format!("wrong position: {}", e))?;
+                Command::Position(r.field, r.to_move)
+            }
+            "play" => Command::Play(move_from_string(arg).ok_or_else(|| format!("wrong move '{}'", arg))?),
+            "go" => {
+                let (ms, goal) = arg.split_once(' ').unwrap_or((arg, ""));
+                let ms = ms.parse().map_err(|_| format!("wrong time '{}'", ms))?;
+                match goal.trim() {
+                    "" => Command::Go(ms, Goal::MostDiscs),
+                    "misere" => Command::Go(ms, Goal::FewestDiscs),
+                    goal => return Err(format!("unknown goal '{}'", goal)),
+                }
+            }
+            "quit" => Command::Quit,
+            _ => return Err(format!("unknown command '{}'", name)),
+        };
+        Ok(command)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Hello => write!(f, "{}", HANDSHAKE),
+            Command::Position(field, to_move) => write!(f, "position {}", ObfRecord::new(field.clone(), *to_move)),
+            Command::Play(mv) => write!(f, "play {}", move_to_string(*mv)),
+            Command::Go(ms, Goal::MostDiscs) => write!(f, "go {}", ms),
+            Command::Go(ms, Goal::FewestDiscs) => write!(f, "go {} misere", ms),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+
+//the engine side: the game as the engine knows it
+pub struct EngineSession {
+    engine: Engine,
+    goal: Goal,
+    field: Field,
+    to_move: OccupyType,
+}
+
+impl EngineSession {
+    //the standard start position until `position`
+    pub fn new(engine: Engine, goal: Goal) -> Self {
+        let mut field = Field::new();
+        field.init();
+        EngineSession { engine, goal, field, to_move: OccupyType::Black }
+    }
+
+    pub fn name(&self) -> String {
+        match self.goal {
+            Goal::MostDiscs => format!("rustversi {}", self.engine),
+            Goal::FewestDiscs => format!("rustversi {} misere", self.engine),
+        }
+    }
+
+    fn play(&mut self, mv: Option<Point>) -> Result<(), String> {
+        let bw = self.to_move;
+        match mv {
+            Some(p) if !self.field.is_valid_move(&p, bw).0 => return Err(format!("{} is not a valid move for {}", p.to_algebraic(), bw)),
+            Some(p) => {
+                self.field.move_in_game(&p, bw);
+            }
+            None if possible_movement(bw, &self.field) => return Err(format!("{} cannot pass, it has a legal move", bw)),
+            None => {}
+        }
+        self.to_move = OccupyType::get_opposite_type(bw);
+        Ok(())
+    }
+
+    //the answer to the line, None for `quit`
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let command = match Command::from_string(line) {
+            Ok(command) => command,
+            Err(e) => return Some(format!("error {}", e)),
+        };
+        let answer = match command {
+            Command::Hello => format!("{} {}", HANDSHAKE, self.name()),
+            Command::Position(field, to_move) => {
+                self.field = field;
+                self.to_move = to_move;
+                "ok".to_string()
+            }
+            Command::Play(mv) => match self.play(mv) {
+                Ok(()) => "ok".to_string(),
+                Err(e) => format!("error {}", e),
+            },
+            //the levels are fixed, the engines of rustversi answer in time on their own; a misère session
+            //plays for the fewest discs whatever `go` says
+            Command::Go(_, goal) => {
+                let goal = if self.goal == Goal::FewestDiscs { self.goal } else { goal };
+                format!("move {}", move_to_string(self.engine.get_move_goal(self.to_move, &self.field, goal)))
+            }
+            Command::Quit => return None,
+        };
+        Some(answer)
+    }
+}
+
+//answers the commands of the input until `quit` or its end
+pub fn serve<R: BufRead, W: Write>(session: &mut EngineSession, input: R, mut output: W) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match session.handle(&line) {
+            Some(answer) => {
+                writeln!(output, "{}", answer)?;
+                output.flush()?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+
+#[test]
+fn protocol_commands_test() {
+    let mut f = Field::new();
+    f.init();
+    for line in ["rvp", "play f5", "play pass", "go 1000", "go 1000 misere", "quit"] {
+        assert_eq!(line, Command::from_string(line).unwrap().to_string());
+    }
+    let position = Command::Position(f, OccupyType::White).to_string();
+    assert_eq!("position ---------------------------OX------XO--------------------------- O;", position);
+    assert!(matches!(Command::from_string(&position), Ok(Command::Position(_, OccupyType::White))));
+    assert!(Command::from_string("play z9").is_err());
+    assert!(Command::from_string("go soon").is_err());
+    assert!(Command::from_string("go 1000 fast").is_err());
+    assert!(Command::from_string("think").is_err());
+}
+
+#[test]
+fn protocol_session_test() {
+    let mut session = EngineSession::new(Engine::Greedy, Goal::MostDiscs);
+    let input = "rvp\nplay f5\n\nplay f5\nplay pass\nbad\ngo 100\nquit\ngo 100\n";
+    let mut output = vec![];
+    serve(&mut session, input.as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(6, lines.len(), "{}", output);
+    assert_eq!(["rvp rustversi greedy", "ok"], lines[..2]);
+    assert!(lines[2].starts_with("error f5 is not a valid move"));
+    assert!(lines[3].starts_with("error O cannot pass"));
+    assert_eq!("error unknown command 'bad'", lines[4]);
+    //the answers of white after f5, each flips one disc
+    assert!(["move d6", "move f6", "move f4"].contains(&lines[5]), "{}", lines[5]);
+
+    //the corner flips 3, f5 4 and g5 1
+    let mut f = Field::new();
+    f.setup_field("
+
+o
+o
+o
+*oooo
+      o
+      *
+");
+    let mut session = EngineSession::new(Engine::Greedy, Goal::MostDiscs);
+    session.handle(&Command::Position(f, OccupyType::Black).to_string());
+    assert_eq!(Some("move g5".to_string()), session.handle("go 100 misere"));
+    assert_eq!(Some("move f5".to_string()), session.handle("go 100"));
+}
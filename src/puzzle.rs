@@ -0,0 +1,344 @@
+/*
+Puzzles: "find the best move" problems for training, a puzzle per line with `;`-separated parts as OBF
+
+    -XXXXX--OOOXXX--OOOXXXXXOOOXXXOO-OOXOXXOOXOOXOXOO-OOOXXOOOOXXXX- X; win; h8 a5 a1; Black to move and win
+
+  position  -- an OBF position (`obf`): 64 cells, a1..h1 first, and the side to move, which is the player
+  goal      -- `win`: the solution wins against any defence, found by searching to the end of the game
+               (at most `MAX_SOLVE_EMPTIES` empty cells); `best[:<depth>]`: the solution moves are the
+               best ones of a search to the depth (default `DEFAULT_BEST_DEPTH`)
+  solution  -- the moves in turn, `f5` or `pass`: the player has to find the 1st, 3rd, ... move, the ones
+               between are the replies of the opponent
+  title     -- optional, e.g. "Black to move and win"
+
+Empty lines and lines starting with `%` are ignored.
+
+A puzzle is sound (`Puzzle::verify`) when every move of the solution is legal, every move of the player is
+the only best one and wins for `win`, and every reply is a best defence.
+`PuzzleSession` grades the answers of the player.
+*/
+use core::fmt;
+
+use crate::point::{Point, move_from_string, move_to_string};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::engine::{score_moves, Goal, MAX_SEARCH_DEPTH};
+use crate::obf::ObfRecord;
+use crate::parser::ParseError;
+
+
+pub const MAX_SOLVE_EMPTIES: u8 = 14;
+pub const DEFAULT_BEST_DEPTH: u8 = 6;
+
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PuzzleGoal {
+    Win,
+    Best(u8),
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub field: Field,
+    //the player
+    pub to_move: OccupyType,
+    pub goal: PuzzleGoal,
+    //None for a pass
+    pub solution: Vec<Option<Point>>,
+    pub title: String,
+}
+
+//the grade of an answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    //not a legal move, the player can try again
+    Illegal,
+    //the puzzle is failed, the expected move
+    Wrong(Option<Point>),
+    //the reply of the opponent, None when the puzzle is solved
+    Correct(Option<Option<Point>>),
+}
+
+
+impl PuzzleGoal {
+    pub fn from_string(s: &str) -> Option<PuzzleGoal> {
+        match s.split_once(':') {
+            None if s == "win" => Some(PuzzleGoal::Win),
+            None if s == "best" => Some(PuzzleGoal::Best(DEFAULT_BEST_DEPTH)),
+            Some(("best", depth)) => depth.parse().ok().filter(|d| (1..=MAX_SEARCH_DEPTH).contains(d)).map(PuzzleGoal::Best),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PuzzleGoal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleGoal::Win => write!(f, "win"),
+            PuzzleGoal::Best(depth) => write!(f, "best:{}", depth),
+        }
+    }
+}
+
+
+fn empties(f: &Field) -> u8 {
+    let (w, b) = f.get_score_wb();
+    let blocked = f.shape().cells().iter().filter(|p| f.get_type(p) == OccupyType::Blocked).count() as u8;
+    f.shape().cells().len() as u8 - w - b - blocked
+}
+
+impl Puzzle {
+    //a line of a puzzle file, errors are reported for line 1
+    pub fn from_string(s: &str) -> Result<Puzzle, ParseError> {
+        let line = s.trim();
+        let err = |part: &str, message: &str| ParseError::new(1, line.find(part).unwrap_or(0) + 1, message);
+        let mut parts = line.splitn(4, ';').skip(1).map(str::trim);
+        let (goal, solution) = match (parts.next(), parts.next()) {
+            (Some(goal), Some(solution)) => (goal, solution),
+            _ => return Err(ParseError::new(1, line.len(), "expected <position>; <goal>; <solution>")),
+        };
+        let title = parts.next().unwrap_or("").to_string();
+        let position = &line[..line.find(';').unwrap_or(line.len())];
+        let r = ObfRecord::from_string(position)?;
+        let goal = PuzzleGoal::from_string(goal).ok_or_else(|| err(goal, &format!("unknown goal '{}'", goal)))?;
+        let solution = solution.split_whitespace()
+            .map(|m| move_from_string(m).ok_or_else(|| err(m, &format!("wrong move '{}'", m))))
+            .collect::<Result<Vec<_>, _>>()?;
+        if solution.is_empty() {
+            return Err(ParseError::new(1, line.len(), "the solution is empty"));
+        }
+        Ok(Puzzle { field: r.field, to_move: r.to_move, goal, solution, title })
+    }
+
+    //the position before the move of the solution
+    fn position(&self, ply: usize) -> Result<(Field, OccupyType), String> {
+        let mut f = self.field.clone();
+        let mut bw = self.to_move;
+        for (i, m) in self.solution.iter().take(ply).enumerate() {
+            match m {
+                Some(p) if f.is_valid_move(p, bw).0 => {
+                    f.move_in_game(p, bw);
+                }
+                None if f.get_list_of_moves(bw).is_empty() => {}
+                _ => return Err(format!("move {} {} is not legal", i + 1, move_to_string(*m))),
+            }
+            bw = OccupyType::get_opposite_type(bw);
+        }
+        Ok((f, bw))
+    }
+
+    //Ok when the puzzle is sound, else what is wrong with it
+    pub fn verify(&self) -> Result<(), String> {
+        let depth = match self.goal {
+            PuzzleGoal::Win => {
+                let n = empties(&self.field);
+                if n > MAX_SOLVE_EMPTIES {
+                    return Err(format!("{} empty cells, a win is checked for {} at most", n, MAX_SOLVE_EMPTIES));
+                }
+                //passes take a ply of the search as well
+                2 * n + 1
+            }
+            PuzzleGoal::Best(depth) => depth,
+        };
+        self.position(self.solution.len())?;
+        for (i, m) in self.solution.iter().enumerate() {
+            let (f, bw) = self.position(i)?;
+            let p = match m {
+                Some(p) => p,
+                None => continue,
+            };
+            let depth = match self.goal {
+                PuzzleGoal::Win => depth - i as u8,
+                PuzzleGoal::Best(depth) => depth,
+            };
+            let scores = score_moves(bw, &f, depth, Goal::MostDiscs);
+            let best = scores[0].1;
+            let score = scores.iter().find(|(q, _)| q == p).map(|(_, s)| *s).expect("a legal move is scored");
+            if i % 2 == 1 {
+                if score < best {
+                    return Err(format!("move {} {} is not a best defence", i + 1, p.to_algebraic()));
+                }
+                continue;
+            }
+            //any win solves a `win` puzzle
+            let (good, other) = match self.goal {
+                PuzzleGoal::Win => (score > 0, scores.iter().find(|(q, s)| q != p && *s > 0)),
+                PuzzleGoal::Best(_) => (score == best, scores.iter().find(|(q, s)| q != p && *s == best)),
+            };
+            if !good {
+                return Err(match self.goal {
+                    PuzzleGoal::Win => format!("move {} {} does not win", i + 1, p.to_algebraic()),
+                    PuzzleGoal::Best(_) => format!("move {} {} is not the best, {} is", i + 1, p.to_algebraic(), scores[0].0.to_algebraic()),
+                });
+            }
+            if let Some((other, _)) = other {
+                return Err(format!("move {} {} is not the only solution, {} is as good", i + 1, p.to_algebraic(), other.to_algebraic()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let position = ObfRecord::new(self.field.clone(), self.to_move).to_string();
+        let solution: Vec<String> = self.solution.iter().map(|m| move_to_string(*m)).collect();
+        write!(f, "{} {}; {}", position, self.goal, solution.join(" "))?;
+        if !self.title.is_empty() {
+            write!(f, "; {}", self.title)?;
+        }
+        Ok(())
+    }
+}
+
+//the puzzles of a file, the errors name the line
+pub fn read_all(s: &str) -> Result<Vec<Puzzle>, ParseError> {
+    let mut puzzles = vec![];
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        let puzzle = Puzzle::from_string(line).map_err(|e| ParseError::new(i + 1, e.column, &e.message))?;
+        puzzles.push(puzzle);
+    }
+    Ok(puzzles)
+}
+
+
+//a puzzle being solved: the answers of the player are graded against the solution
+pub struct PuzzleSession<'a> {
+    puzzle: &'a Puzzle,
+    //the number of the solution moves played
+    ply: usize,
+    field: Field,
+    to_move: OccupyType,
+    failed: bool,
+}
+
+impl<'a> PuzzleSession<'a> {
+    pub fn new(puzzle: &'a Puzzle) -> Self {
+        PuzzleSession { puzzle, ply: 0, field: puzzle.field.clone(), to_move: puzzle.to_move, failed: false }
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn to_move(&self) -> OccupyType {
+        self.to_move
+    }
+
+    pub fn is_solved(&self) -> bool {
+        !self.failed && self.ply >= self.puzzle.solution.len()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.failed || self.ply >= self.puzzle.solution.len()
+    }
+
+    fn play(&mut self, mv: Option<Point>) {
+        if let Some(p) = mv {
+            self.field.move_in_game(&p, self.to_move);
+        }
+        self.to_move = OccupyType::get_opposite_type(self.to_move);
+        self.ply += 1;
+    }
+
+    //the move of the player, None for a pass; the reply is played at once
+    pub fn answer(&mut self, mv: Option<Point>) -> Answer {
+        if self.is_over() {
+            return Answer::Wrong(None);
+        }
+        let legal = match mv {
+            Some(p) => self.field.is_valid_move(&p, self.to_move).0,
+            None => self.field.get_list_of_moves(self.to_move).is_empty(),
+        };
+        if !legal {
+            return Answer::Illegal;
+        }
+        let expected = self.puzzle.solution[self.ply];
+        if mv != expected {
+            self.failed = true;
+            return Answer::Wrong(expected);
+        }
+        self.play(mv);
+        let reply = self.puzzle.solution.get(self.ply).copied();
+        if let Some(reply) = reply {
+            self.play(reply);
+        }
+        Answer::Correct(reply)
+    }
+}
+
+
+#[cfg(test)]
+const PUZZLE_WIN: &str = "-XXXXX--OOOXXX--OOOXXXXXOOOXXXOO-OOXOXXOOXOOXOXOO-OOOXXOOOOXXXX- X; win; h8 a5 a1; Black to move and win";
+
+#[cfg(test)]
+fn alg(s: &str) -> Point {
+    Point::from_algebraic(s).unwrap()
+}
+
+#[test]
+fn puzzle_parse_test() {
+    let p = Puzzle::from_string(PUZZLE_WIN).unwrap();
+    assert_eq!(OccupyType::Black, p.to_move);
+    assert_eq!(PuzzleGoal::Win, p.goal);
+    assert_eq!(vec![Some(alg("h8")), Some(alg("a5")), Some(alg("a1"))], p.solution);
+    assert_eq!("Black to move and win", p.title);
+    assert_eq!(PUZZLE_WIN, p.to_string());
+    assert_eq!(Some(PuzzleGoal::Best(3)), PuzzleGoal::from_string("best:3"));
+    assert_eq!(Some(PuzzleGoal::Best(DEFAULT_BEST_DEPTH)), PuzzleGoal::from_string("best"));
+    assert_eq!(None, PuzzleGoal::from_string("best:0"));
+
+    assert!(read_all("% puzzles\n\n").unwrap().is_empty());
+    let err = read_all(&format!("{}\n{}", PUZZLE_WIN, PUZZLE_WIN.replace("win;", "draw;"))).unwrap_err();
+    assert_eq!(2, err.line);
+    assert!(read_all(&PUZZLE_WIN.replace("a5", "z9")).is_err());
+    assert!(read_all(&PUZZLE_WIN.replace("h8 a5 a1", "")).is_err());
+    assert!(read_all("---- X; win; a1").is_err());
+}
+
+#[test]
+fn puzzle_verify_test() {
+    let p = Puzzle::from_string(PUZZLE_WIN).unwrap();
+    assert_eq!(Ok(()), p.verify());
+    assert_eq!(Ok(()), Puzzle::from_string(&PUZZLE_WIN.replace("win;", "best:4;")).unwrap().verify());
+
+    //a move which does not win
+    let (f, bw) = (p.field.clone(), p.to_move);
+    let (lost, _) = score_moves(bw, &f, 20, Goal::MostDiscs).into_iter().find(|(_, s)| *s <= 0).unwrap();
+    let wrong = Puzzle { solution: vec![Some(lost)], ..p.clone() };
+    assert_eq!(Err(format!("move 1 {} does not win", lost.to_algebraic())), wrong.verify());
+    let illegal = Puzzle { solution: vec![Some(alg("b1"))], ..p.clone() };
+    assert_eq!(Err("move 1 b1 is not legal".to_string()), illegal.verify());
+    //a reply which loses more than a5
+    let mut after = f.clone();
+    after.move_in_game(&alg("h8"), bw);
+    let replies = score_moves(OccupyType::White, &after, 4, Goal::MostDiscs);
+    let (worse, _) = *replies.last().unwrap();
+    assert!(replies.last().unwrap().1 < replies[0].1);
+    let defence = Puzzle { solution: vec![Some(alg("h8")), Some(worse)], goal: PuzzleGoal::Best(4), ..p.clone() };
+    assert_eq!(Err(format!("move 2 {} is not a best defence", worse.to_algebraic())), defence.verify());
+    let many = Puzzle::from_string("-OOOOOOOOOOOOOO-OOOXXXXXOOOOOX-XOOOOX-OXOOOXOXXOO-OOXXO--XOOXXO- X; best:4; a1 h2 a8").unwrap();
+    assert!(many.verify().unwrap_err().starts_with("move 1 a1 is not the best"));
+}
+
+#[test]
+fn puzzle_session_test() {
+    let p = Puzzle::from_string(PUZZLE_WIN).unwrap();
+    let mut session = PuzzleSession::new(&p);
+    assert_eq!(Answer::Illegal, session.answer(Some(alg("b1"))));
+    assert_eq!(Answer::Illegal, session.answer(None));
+    assert_eq!(Answer::Correct(Some(Some(alg("a5")))), session.answer(Some(alg("h8"))));
+    assert!(!session.is_over());
+    assert_eq!(OccupyType::Black, session.to_move());
+    assert_eq!(OccupyType::White, session.field().get_type(&alg("a5")));
+    assert_eq!(Answer::Correct(None), session.answer(Some(alg("a1"))));
+    assert!(session.is_solved());
+
+    let mut session = PuzzleSession::new(&p);
+    assert_eq!(Answer::Wrong(Some(alg("h8"))), session.answer(Some(alg("a1"))));
+    assert!(session.is_over() && !session.is_solved());
+}
@@ -0,0 +1,478 @@
+/*
+Annotated game record, PGN-like: tag pairs, an empty line and the move text
+
+    [Event "Club match"]
+    [Date "2024.05.01"]
+    [Black "Alice"]
+    [White "rustversi"]
+    [WhiteEngine "search:4"]
+    [Result "*"]
+
+    {Opening} 1. f5 {the usual start [%eval +0] [%time 2.500]} d6 2. c3 d3 3. c4 *
+
+  tags      -- `[Name "value"]`, `\"` and `\\` are escaped in the value. Known tags:
+               Event, Site, Date, Round, Black, White, Result (`<black discs>-<white discs>` or `*`),
+               BlackEngine, WhiteEngine (`Engine::from_string`), Setup (an OBF line with the start
+               position and the side to move; without it the game starts from the position of the rules,
+               black first), Rules (`Rules::from_string`, standard without it; the Result counts the discs
+               for `misere` as well, the side with fewer discs wins)
+  moves     -- algebraic squares (`a1` is the top left corner) or `pass`, the move numbers are optional
+  comments  -- `{...}` after a move (or before the first one for the whole game); `[%eval <score>]` and
+               `[%time <seconds>]` inside a comment are the evaluation and the time spent on the move
+  result    -- `*` or `<black>-<white>` ends the move text
+
+Lines starting with `;` are ignored.
+*/
+use core::fmt;
+
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::engine::Engine;
+use crate::obf::ObfRecord;
+use crate::rules::Rules;
+use crate::parser::ParseError;
+
+
+const LINE_WIDTH: usize = 79;
+
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RecordMove {
+    //None for a pass
+    pub mv: Option<Point>,
+    pub comment: Option<String>,
+    pub eval: Option<i32>,
+    pub time_ms: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GameRecord {
+    //in the order of the file
+    pub tags: Vec<(String, String)>,
+    //the comment before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<RecordMove>,
+}
+
+
+impl RecordMove {
+    pub fn new(mv: Option<Point>) -> Self {
+        RecordMove { mv, ..Default::default() }
+    }
+}
+
+
+//the source text with the position of the next char
+struct Cursor<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(s: &'a str) -> Self {
+        Cursor { chars: s.chars().peekable(), line: 1, column: 1 }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(self.line, self.column, message)
+    }
+
+    //whitespace and `;` comment lines
+    fn skip_space(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                while !matches!(self.bump(), Some('\n') | None) {}
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut ret = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '{' || c == '[' {
+                break;
+            }
+            ret.push(c);
+            self.bump();
+        }
+        ret
+    }
+}
+
+
+fn parse_tag(cur: &mut Cursor) -> Result<(String, String), ParseError> {
+    cur.bump();
+    let name = cur.word();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(cur.error("expected a tag name"));
+    }
+    cur.skip_space();
+    if cur.bump() != Some('"') {
+        return Err(cur.error("expected a quoted tag value"));
+    }
+    let mut value = String::new();
+    loop {
+        match cur.bump() {
+            Some('\\') => match cur.bump() {
+                Some(c) => value.push(c),
+                None => return Err(cur.error("unterminated tag value")),
+            },
+            Some('"') => break,
+            Some('\n') | None => return Err(cur.error("unterminated tag value")),
+            Some(c) => value.push(c),
+        }
+    }
+    cur.skip_space();
+    if cur.bump() != Some(']') {
+        return Err(cur.error("expected ']'"));
+    }
+    Ok((name, value))
+}
+
+//the text of a comment and its `[%eval ...]` / `[%time ...]` commands
+fn parse_comment(cur: &mut Cursor, mv: &mut RecordMove) -> Result<Option<String>, ParseError> {
+    let (line, column) = (cur.line, cur.column);
+    cur.bump();
+    let mut text = String::new();
+    loop {
+        match cur.bump() {
+            Some('}') => break,
+            Some(c) => text.push(c),
+            None => return Err(ParseError::new(line, column, "unterminated comment")),
+        }
+    }
+    let err = |msg: String| ParseError::new(line, column, &msg);
+    let mut rest = String::new();
+    let mut s = text.as_str();
+    while let Some(start) = s.find("[%") {
+        rest.push_str(&s[..start]);
+        let end = s[start..].find(']').ok_or_else(|| err("unterminated command".to_string()))? + start;
+        let command = &s[start + 2..end];
+        let (name, value) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let value = value.trim();
+        match name {
+            "eval" => mv.eval = Some(value.parse().map_err(|_| err(format!("wrong evaluation '{}'", value)))?),
+            "time" => {
+                let seconds: f64 = value.parse().map_err(|_| err(format!("wrong time '{}'", value)))?;
+                if seconds < 0.0 {
+                    return Err(err(format!("wrong time '{}'", value)));
+                }
+                mv.time_ms = Some((seconds * 1000.0).round() as u32);
+            }
+            //unknown commands stay in the text
+            _ => rest.push_str(&s[start..=end]),
+        }
+        s = &s[end + 1..];
+    }
+    rest.push_str(s);
+    let rest = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+    Ok(if rest.is_empty() { None } else { Some(rest) })
+}
+
+fn is_result(word: &str) -> bool {
+    if word == "*" {
+        return true;
+    }
+    match word.split_once('-') {
+        Some((b, w)) => b.parse::<u8>().is_ok() && w.parse::<u8>().is_ok(),
+        None => false,
+    }
+}
+
+fn is_move_number(word: &str) -> bool {
+    let digits = word.trim_end_matches('.');
+    digits.len() < word.len() && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+//the closing brace cannot be escaped in a comment
+fn comment_text(s: &str) -> String {
+    s.replace('}', ")")
+}
+
+
+impl GameRecord {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    //replaces the value of an existing tag, keeping its place
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    //the computer player of the side, None for a human or an unknown engine
+    pub fn engine(&self, bw: OccupyType) -> Option<Engine> {
+        let name = if bw == OccupyType::White { "WhiteEngine" } else { "BlackEngine" };
+        self.tag(name).and_then(Engine::from_string)
+    }
+
+    //the variant of the Rules tag, standard without it
+    pub fn rules(&self) -> Result<Rules, String> {
+        match self.tag("Rules") {
+            Some(rules) => Rules::from_string(rules).map_err(|e| format!("wrong Rules tag: {}", e)),
+            None => Ok(Rules::new()),
+        }
+    }
+
+    //the position and the side to move before the first move
+    pub fn start(&self) -> Result<(Field, OccupyType), String> {
+        let rules = self.rules()?;
+        match self.tag("Setup") {
+            Some(setup) => {
+                let mut r = ObfRecord::from_string(setup).map_err(|e| format!("wrong Setup tag: {}", e))?;
+                r.field.set_shape(rules.shape);
+                Ok((r.field, r.to_move))
+            }
+            None => Ok((rules.field(), OccupyType::Black)),
+        }
+    }
+
+    pub fn set_start(&mut self, field: &Field, to_move: OccupyType) {
+        let setup = ObfRecord::new(field.clone(), to_move).to_string();
+        self.set_tag("Setup", &setup);
+    }
+
+    //the final position and the side to move, an error on an illegal move or pass
+    pub fn replay(&self) -> Result<(Field, OccupyType), String> {
+        let (mut f, mut bw) = self.start()?;
+        for (i, m) in self.moves.iter().enumerate() {
+            let legal = !f.get_list_of_moves(bw).is_empty();
+            match m.mv {
+                Some(p) => {
+                    if !f.is_valid_move(&p, bw).0 {
+                        return Err(format!("move {} {} is not valid for {}", i + 1, p.to_algebraic(), bw));
+                    }
+                    f.move_in_game(&p, bw);
+                }
+                None if legal => return Err(format!("move {}: {} cannot pass, it has a legal move", i + 1, bw)),
+                None => {}
+            }
+            bw = OccupyType::get_opposite_type(bw);
+        }
+        Ok((f, bw))
+    }
+
+    //`<black>-<white>` of the field
+    pub fn result_of(field: &Field) -> String {
+        let (w, b) = field.get_score_wb();
+        format!("{}-{}", b, w)
+    }
+
+    pub fn from_string(s: &str) -> Result<GameRecord, ParseError> {
+        let mut record = GameRecord::new();
+        let mut cur = Cursor::new(s);
+
+        cur.skip_space();
+        while cur.peek() == Some('[') {
+            let tag = parse_tag(&mut cur)?;
+            record.tags.push(tag);
+            cur.skip_space();
+        }
+
+        loop {
+            cur.skip_space();
+            let (line, column) = (cur.line, cur.column);
+            match cur.peek() {
+                None => break,
+                Some('{') => {
+                    match record.moves.last_mut() {
+                        Some(last) => {
+                            let text = parse_comment(&mut cur, last)?;
+                            last.comment = text;
+                        }
+                        None => {
+                            let mut game = RecordMove::default();
+                            record.comment = parse_comment(&mut cur, &mut game)?;
+                        }
+                    }
+                    continue;
+                }
+                Some('[') => return Err(cur.error("tags must come before the moves")),
+                _ => {}
+            }
+            let word = cur.word();
+            if is_result(&word) {
+                cur.skip_space();
+                if cur.peek().is_some() {
+                    return Err(cur.error("unexpected text after the result"));
+                }
+                break;
+            }
+            if is_move_number(&word) {
+                continue;
+            }
+            let mv = match word.as_str() {
+                "pass" | "PA" | "--" => None,
+                _ => Some(Point::from_algebraic(&word)
+                    .ok_or_else(|| ParseError::new(line, column, &format!("wrong move '{}'", word)))?),
+            };
+            record.moves.push(RecordMove::new(mv));
+        }
+        Ok(record)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut tokens = vec![];
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment_text(comment)));
+        }
+        for (i, m) in self.moves.iter().enumerate() {
+            if i % 2 == 0 {
+                tokens.push(format!("{}.", i / 2 + 1));
+            }
+            tokens.push(match m.mv {
+                Some(p) => p.to_algebraic(),
+                None => "pass".to_string(),
+            });
+            let mut notes = vec![];
+            if let Some(comment) = &m.comment {
+                notes.push(comment_text(comment));
+            }
+            if let Some(eval) = m.eval {
+                notes.push(format!("[%eval {:+}]", eval));
+            }
+            if let Some(ms) = m.time_ms {
+                notes.push(format!("[%time {}.{:03}]", ms / 1000, ms % 1000));
+            }
+            if !notes.is_empty() {
+                tokens.push(format!("{{{}}}", notes.join(" ")));
+            }
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+
+        //comments may be longer than a line, they are not broken
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+
+#[cfg(test)]
+fn sample_record() -> GameRecord {
+    let mut record = GameRecord::new();
+    record.set_tag("Event", "Test \"quoted\" game");
+    record.set_tag("Black", "Alice");
+    record.set_tag("White", "rustversi");
+    record.set_tag("WhiteEngine", "search:3");
+    record.comment = Some("Opening".to_string());
+    for (i, m) in ["f5", "d6", "c3", "d3", "c4"].iter().enumerate() {
+        let mut mv = RecordMove::new(Point::from_algebraic(m));
+        if i == 1 {
+            mv.comment = Some("the tiger".to_string());
+            mv.eval = Some(-2);
+            mv.time_ms = Some(1250);
+        }
+        record.moves.push(mv);
+    }
+    let (f, _) = record.replay().unwrap();
+    record.set_tag("Result", &GameRecord::result_of(&f));
+    record
+}
+
+#[test]
+fn record_round_trip_test() {
+    let record = sample_record();
+    let s = record.to_string();
+    assert!(s.contains("[Event \"Test \\\"quoted\\\" game\"]\n"));
+    assert!(s.contains("1. f5 d6 {the tiger [%eval -2] [%time 1.250]} 2. c3 d3 3. c4 6-3\n"));
+    let parsed = GameRecord::from_string(&s).unwrap();
+    assert_eq!(record, parsed);
+    assert_eq!(s, parsed.to_string());
+    assert_eq!(Some(Engine::Search(3)), parsed.engine(OccupyType::White));
+    assert_eq!(None, parsed.engine(OccupyType::Black));
+}
+
+#[test]
+fn record_setup_test() {
+    //white moves first in the interactive game
+    let mut record = GameRecord::new();
+    let (f, _) = record.start().unwrap();
+    record.set_start(&f, OccupyType::White);
+    record.moves.push(RecordMove::new(Some(Point::new(5, 6))));
+    let parsed = GameRecord::from_string(&record.to_string()).unwrap();
+    let (f, bw) = parsed.replay().unwrap();
+    assert_eq!(OccupyType::Black, bw);
+    assert_eq!(OccupyType::White, f.get_type(&Point::new(5, 6)));
+
+    //passing with a legal move
+    record.moves.push(RecordMove::new(None));
+    assert!(record.replay().unwrap_err().contains("cannot pass"));
+
+    //the start position of the rules without a Setup tag
+    let mut record = GameRecord::new();
+    record.set_tag("Rules", "parallel,misere");
+    let parsed = GameRecord::from_string(&record.to_string()).unwrap();
+    assert!(parsed.rules().unwrap().misere);
+    let (f, bw) = parsed.start().unwrap();
+    assert_eq!(OccupyType::Black, bw);
+    assert_eq!(Rules::from_string("parallel").unwrap().field().serialize(), f.serialize());
+    record.set_tag("Rules", "hexagonal");
+    assert!(record.start().unwrap_err().starts_with("wrong Rules tag"));
+}
+
+#[test]
+fn record_parse_test() {
+    let record = GameRecord::from_string("; no tags\nf5 {[%time 3] fast [%clk 0:10]} pass 2... d6 *").unwrap();
+    assert_eq!(3, record.moves.len());
+    assert_eq!(Some(3000), record.moves[0].time_ms);
+    assert_eq!(Some("fast [%clk 0:10]".to_string()), record.moves[0].comment);
+    assert_eq!(None, record.moves[1].mv);
+
+    let err = GameRecord::from_string("[Event \"x\"]\n\n1. f5 z9").unwrap_err();
+    assert_eq!(ParseError::new(3, 7, "wrong move 'z9'"), err);
+    let err = GameRecord::from_string("1. f5 {open").unwrap_err();
+    assert_eq!((1, 7), (err.line, err.column));
+    assert!(GameRecord::from_string("[Event x]").is_err());
+    assert!(GameRecord::from_string("f5 * d6").is_err());
+}
@@ -0,0 +1,216 @@
+/*
+Text renderers of the board. `Style` picks one of the built-in renderers by name:
+
+  classic         -- `Field::to_string`, the tall ASCII grid
+  compact         -- 8 lines of `#`, `O` and `.` (`@` for a blocked cell), row 8 first
+  unicode         -- ● black, ○ white, · empty, ■ blocked in a box-drawing frame with the coordinates
+  annotated[:<c>] -- the legal moves of the side are marked with their flip counts (`+` for 10 and more)
+                     and listed under the board; without `:<c>` the side to move is annotated
+
+The compact and unicode forms are accepted by `parser::parse_board`.
+*/
+use core::fmt;
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+
+
+pub trait Renderer {
+    fn render(&self, f: &Field) -> String;
+}
+
+pub struct Classic;
+pub struct Compact;
+pub struct Unicode;
+pub struct Annotated {
+    pub side: OccupyType,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Style {
+    #[default]
+    Classic,
+    Compact,
+    Unicode,
+    //None for the side to move
+    Annotated(Option<OccupyType>),
+}
+
+
+fn column_labels(indent: usize) -> String {
+    let labels: Vec<String> = (1..=BOARD_SIZE).map(|x| x.to_string()).collect();
+    format!("{}{}\n", " ".repeat(indent), labels.join(" "))
+}
+
+fn side_from_str(s: &str) -> Option<OccupyType> {
+    match s.to_lowercase().as_str() {
+        "#" | "x" | "black" | "b" => Some(OccupyType::Black),
+        "o" | "white" | "w" => Some(OccupyType::White),
+        _ => None,
+    }
+}
+
+
+impl Renderer for Classic {
+    fn render(&self, f: &Field) -> String {
+        f.to_string()
+    }
+}
+
+impl Renderer for Compact {
+    fn render(&self, f: &Field) -> String {
+        let mut ret = String::new();
+        for y in (1..=BOARD_SIZE).rev() {
+            for x in 1..=BOARD_SIZE {
+                ret.push(match f.get_type(&Point::new(x, y)) {
+                    OccupyType::Black => '#',
+                    OccupyType::White => 'O',
+                    OccupyType::Blocked => '@',
+                    _ => '.',
+                });
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+}
+
+impl Renderer for Unicode {
+    fn render(&self, f: &Field) -> String {
+        let width = BOARD_SIZE as usize * 2 + 1;
+        let mut ret = column_labels(4);
+        ret.push_str(&format!("  ┌{}┐\n", "─".repeat(width)));
+        for y in (1..=BOARD_SIZE).rev() {
+            ret.push_str(&format!("{} │", y));
+            for x in 1..=BOARD_SIZE {
+                ret.push_str(match f.get_type(&Point::new(x, y)) {
+                    OccupyType::Black => " ●",
+                    OccupyType::White => " ○",
+                    OccupyType::Blocked => " ■",
+                    _ => " ·",
+                });
+            }
+            ret.push_str(&format!(" │ {}\n", y));
+        }
+        ret.push_str(&format!("  └{}┘\n", "─".repeat(width)));
+        ret.push_str(&column_labels(4));
+        ret
+    }
+}
+
+impl Renderer for Annotated {
+    fn render(&self, f: &Field) -> String {
+        let moves = f.get_list_of_moves(self.side);
+        let mut ret = column_labels(3);
+        for y in (1..=BOARD_SIZE).rev() {
+            ret.push_str(&format!("{} ", y));
+            for x in 1..=BOARD_SIZE {
+                let p = Point::new(x, y);
+                let cell = match f.get_type(&p) {
+                    OccupyType::Black => '#',
+                    OccupyType::White => 'O',
+                    OccupyType::Blocked => '@',
+                    _ => match moves.iter().find(|(q, _)| *q == p) {
+                        Some((_, n)) if *n < 10 => (b'0' + n) as char,
+                        Some(_) => '+',
+                        None => '.',
+                    },
+                };
+                ret.push(' ');
+                ret.push(cell);
+            }
+            ret.push_str(&format!("  {}\n", y));
+        }
+        ret.push_str(&column_labels(3));
+        if moves.is_empty() {
+            ret.push_str(&format!("no legal moves of {}\n", self.side));
+        } else {
+            let list: Vec<String> = moves.iter().map(|(p, n)| format!("{} {}", p, n)).collect();
+            ret.push_str(&format!("legal moves of {} (flips): {}\n", self.side, list.join(", ")));
+        }
+        ret
+    }
+}
+
+
+impl Style {
+    //"classic", "compact", "unicode", "annotated" or "annotated:<color>"
+    pub fn from_string(s: &str) -> Option<Style> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "classic" => return Some(Style::Classic),
+            "compact" => return Some(Style::Compact),
+            "unicode" => return Some(Style::Unicode),
+            "annotated" => return Some(Style::Annotated(None)),
+            _ => {}
+        }
+        let side = side_from_str(s.strip_prefix("annotated:")?)?;
+        Some(Style::Annotated(Some(side)))
+    }
+
+    pub fn renderer(&self, to_move: OccupyType) -> Box<dyn Renderer> {
+        match self {
+            Style::Classic => Box::new(Classic),
+            Style::Compact => Box::new(Compact),
+            Style::Unicode => Box::new(Unicode),
+            Style::Annotated(side) => Box::new(Annotated { side: side.unwrap_or(to_move) }),
+        }
+    }
+
+    pub fn render(&self, f: &Field, to_move: OccupyType) -> String {
+        self.renderer(to_move).render(f)
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Style::Classic => write!(f, "classic"),
+            Style::Compact => write!(f, "compact"),
+            Style::Unicode => write!(f, "unicode"),
+            Style::Annotated(None) => write!(f, "annotated"),
+            Style::Annotated(Some(side)) => write!(f, "annotated:{}", side),
+        }
+    }
+}
+
+
+#[test]
+fn render_styles_test() {
+    let mut f = Field::new();
+    f.init();
+    let compact = Style::Compact.render(&f, OccupyType::Black);
+    assert_eq!(8, compact.lines().count());
+    assert_eq!("...O#...", compact.lines().nth(3).unwrap());
+
+    let unicode = Style::Unicode.render(&f, OccupyType::Black);
+    assert_eq!("    1 2 3 4 5 6 7 8", unicode.lines().next().unwrap());
+    assert_eq!("5 │ · · · ○ ● · · · │ 5", unicode.lines().nth(5).unwrap());
+    for s in [compact, unicode] {
+        assert_eq!(f.serialize(), crate::parser::parse_board(&s).unwrap().serialize());
+    }
+    assert_eq!(f.to_string(), Style::default().render(&f, OccupyType::Black));
+}
+
+#[test]
+fn render_annotated_test() {
+    let mut f = Field::new();
+    f.init();
+    let s = Style::Annotated(None).render(&f, OccupyType::White);
+    assert_eq!("6  . . . . 1 . . .  6", s.lines().nth(3).unwrap());
+    assert!(s.ends_with("legal moves of O (flips): (3,4) 1, (4,3) 1, (5,6) 1, (6,5) 1\n"));
+
+    let empty = Field::new();
+    assert!(Style::Annotated(Some(OccupyType::Black)).render(&empty, OccupyType::White).ends_with("no legal moves of #\n"));
+}
+
+#[test]
+fn render_style_names_test() {
+    for s in ["classic", "compact", "unicode", "annotated", "annotated:O", "annotated:#"] {
+        assert_eq!(s, Style::from_string(s).unwrap().to_string());
+    }
+    assert_eq!(Some(Style::Annotated(Some(OccupyType::White))), Style::from_string("Annotated:white"));
+    assert_eq!(None, Style::from_string("annotated:green"));
+    assert_eq!(None, Style::from_string("fancy"));
+}
@@ -0,0 +1,204 @@
+/*
+Replay of a finished (or unfinished) game for reviewing: all the positions are computed once,
+then the caller steps through them.
+
+    let mut r = Replay::from_moves(&moves, OccupyType::White)?;
+    r.to_end();
+    while r.back() {
+        println!("{}", r.field());
+    }
+
+Ply 0 is the start position, ply n is the position after the n-th move. A pass is a ply too;
+a side without a legal move passes even if the pass is not in the moves.
+*/
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::game::possible_movement;
+
+
+#[derive(Debug, Clone)]
+pub struct Ply {
+    //None for a pass
+    pub mv: Option<Point>,
+    pub side: OccupyType,
+    //the discs flipped by the move
+    pub flips: Vec<Point>,
+    //the position after the move
+    pub field: Field,
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    start: Field,
+    first: OccupyType,
+    plies: Vec<Ply>,
+    current: usize,
+}
+
+
+impl Replay {
+    //an error on an illegal move or pass, the replay is at the start
+    pub fn new(start: &Field, first: OccupyType, moves: &[Option<Point>]) -> Result<Replay, String> {
+        let mut f = start.clone();
+        let mut bw = first;
+        let mut plies = vec![];
+        for (i, m) in moves.iter().enumerate() {
+            let legal = possible_movement(bw, &f);
+            match m {
+                Some(_) if !legal => {
+                    plies.push(Ply { mv: None, side: bw, flips: vec![], field: f.clone() });
+                    bw = OccupyType::get_opposite_type(bw);
+                }
+                None if legal => return Err(format!("move {}: {} cannot pass, it has a legal move", i + 1, bw)),
+                _ => {}
+            }
+            let flips = match m {
+                Some(p) => {
+                    if !f.is_valid_move(p, bw).0 {
+                        return Err(format!("move {} {} is not valid for {}", i + 1, p, bw));
+                    }
+                    let flips = f.get_opposite_points(p, bw);
+                    f.move_in_game(p, bw);
+                    flips
+                }
+                None => vec![],
+            };
+            plies.push(Ply { mv: *m, side: bw, flips, field: f.clone() });
+            bw = OccupyType::get_opposite_type(bw);
+        }
+        Ok(Replay { start: start.clone(), first, plies, current: 0 })
+    }
+
+    //from the standard position
+    pub fn from_moves(moves: &[Option<Point>], first: OccupyType) -> Result<Replay, String> {
+        let mut f = Field::new();
+        f.init();
+        Replay::new(&f, first, moves)
+    }
+
+    //the number of plies, the last one is `len()`
+    pub fn len(&self) -> usize {
+        self.plies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plies.is_empty()
+    }
+
+    pub fn plies(&self) -> &[Ply] {
+        &self.plies
+    }
+
+    //the current ply
+    pub fn ply(&self) -> usize {
+        self.current
+    }
+
+    //false at the end
+    pub fn forward(&mut self) -> bool {
+        self.jump(self.current + 1)
+    }
+
+    //false at the start
+    pub fn back(&mut self) -> bool {
+        self.current > 0 && self.jump(self.current - 1)
+    }
+
+    //false (and the ply is not changed) if there is no such ply
+    pub fn jump(&mut self, ply: usize) -> bool {
+        if ply > self.plies.len() {
+            return false;
+        }
+        self.current = ply;
+        true
+    }
+
+    pub fn to_start(&mut self) {
+        self.current = 0;
+    }
+
+    pub fn to_end(&mut self) {
+        self.current = self.plies.len();
+    }
+
+    //the move which led to the current position, None at the start
+    pub fn last_ply(&self) -> Option<&Ply> {
+        self.current.checked_sub(1).map(|i| &self.plies[i])
+    }
+
+    pub fn field(&self) -> &Field {
+        self.last_ply().map_or(&self.start, |ply| &ply.field)
+    }
+
+    //(white, black) as `FieldAction::get_score_wb`
+    pub fn score(&self) -> (u8, u8) {
+        self.field().get_score_wb()
+    }
+
+    //the discs flipped by the last move
+    pub fn flips(&self) -> &[Point] {
+        self.last_ply().map_or(&[], |ply| &ply.flips)
+    }
+
+    //the side of the next ply
+    pub fn to_move(&self) -> OccupyType {
+        self.last_ply().map_or(self.first, |ply| OccupyType::get_opposite_type(ply.side))
+    }
+}
+
+
+#[cfg(test)]
+fn moves_of(s: &str) -> Vec<Option<Point>> {
+    s.split_whitespace().map(|m| if m == "pass" { None } else { Point::from_string(m) }).collect()
+}
+
+#[test]
+fn replay_step_test() {
+    let mut r = Replay::from_moves(&moves_of("6,4 6,3 5,3"), OccupyType::Black).unwrap();
+    assert_eq!(3, r.len());
+    assert_eq!(0, r.ply());
+    assert!(!r.back());
+    assert_eq!((2, 2), r.score());
+    assert!(r.flips().is_empty());
+
+    assert!(r.forward());
+    assert_eq!((1, 4), r.score());
+    assert_eq!(vec![Point::new(5, 4)], r.flips());
+    assert_eq!(OccupyType::White, r.to_move());
+
+    r.to_end();
+    assert!(!r.forward());
+    assert_eq!(3, r.ply());
+    assert_eq!(Some(Point::new(5, 3)), r.last_ply().unwrap().mv);
+    let end = r.field().serialize();
+
+    assert!(r.jump(1));
+    assert!(!r.jump(4));
+    assert_eq!(1, r.ply());
+    assert!(r.forward() && r.forward());
+    assert_eq!(end, r.field().serialize());
+    r.to_start();
+    let mut start = Field::new();
+    start.init();
+    assert_eq!(start.serialize(), r.field().serialize());
+}
+
+#[test]
+fn replay_pass_test() {
+    //white has no move after the black one
+    let mut f = Field::new();
+    f.set_type(&Point::new(1, 1), OccupyType::Black);
+    f.set_type(&Point::new(2, 1), OccupyType::White);
+    f.set_type(&Point::new(8, 8), OccupyType::Black);
+    f.set_type(&Point::new(8, 7), OccupyType::White);
+    let r = Replay::new(&f, OccupyType::Black, &moves_of("3,1 8,6")).unwrap();
+    //the implicit pass of white is a ply
+    assert_eq!(3, r.len());
+    assert_eq!(None, r.plies()[1].mv);
+    assert_eq!(OccupyType::White, r.plies()[1].side);
+    assert_eq!(OccupyType::Black, r.plies()[2].side);
+
+    assert!(Replay::new(&f, OccupyType::Black, &moves_of("pass")).is_err());
+    assert!(Replay::from_moves(&moves_of("1,1"), OccupyType::Black).unwrap_err().starts_with("move 1"));
+}
@@ -0,0 +1,282 @@
+/*
+Rule variants: the starting position, handicap discs, blocked cells and the misère result. Written as comma-separated words:
+
+  standard             -- the four discs on the diagonals (`FieldAction::init`), the default
+  parallel             -- the same colors side by side: black on d4 e4, white on d5 e5
+  custom:<board>       -- any position, the 64 cells of `X`, `O` and `-` (a1..h1 first as in OBF), `@` for a
+                          blocked cell; a board template with holes
+  blocked:<cells>      -- blocked cells (holes) added to the start, algebraic and joined with `+`: `blocked:a1+d3`
+  handicap:<n>[:<c>]   -- n (1..4) corners for the weaker side (black by default) in the order a1, h8, h1, a8
+  misere               -- anti-reversi: the side with fewer discs wins
+  shape:<shape>        -- the board shape (`shape::Shape`): `shape:octagon`, `shape:rect:6x8`; the handicap corners
+                          outside of it are left out
+
+e.g. `parallel,handicap:2:O`. The scoring is the disc count of the side, handicap discs included.
+*/
+use core::fmt;
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::parser::parse_board;
+use crate::engine::Goal;
+use crate::shape::Shape;
+
+
+pub const MAX_HANDICAP: u8 = 4;
+//(1,8) a1, (8,1) h8, (8,8) h1, (1,1) a8
+const HANDICAP_CORNERS: [(u8, u8); 4] = [(1, 8), (8, 1), (8, 8), (1, 1)];
+
+
+#[derive(Debug, Clone, Default)]
+pub enum Start {
+    #[default]
+    Standard,
+    Parallel,
+    Custom(Field),
+}
+
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub start: Start,
+    //the number of corners
+    pub handicap: u8,
+    pub handicap_side: OccupyType,
+    pub misere: bool,
+    //holes, no disc may be placed there
+    pub blocked: Vec<Point>,
+    pub shape: Shape,
+}
+
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules { start: Start::Standard, handicap: 0, handicap_side: OccupyType::Black, misere: false, blocked: vec![], shape: Shape::Square }
+    }
+}
+
+fn side_from_str(s: &str) -> Option<OccupyType> {
+    match s.to_lowercase().as_str() {
+        "#" | "x" | "black" | "b" => Some(OccupyType::Black),
+        "o" | "white" | "w" => Some(OccupyType::White),
+        _ => None,
+    }
+}
+
+//64 cells of `X`, `O` and `-`, row 8 first
+fn board_to_string(f: &Field) -> String {
+    let mut ret = String::new();
+    for y in (1..=BOARD_SIZE).rev() {
+        for x in 1..=BOARD_SIZE {
+            ret.push(match f.get_type(&Point::new(x, y)) {
+                OccupyType::Black => 'X',
+                OccupyType::White => 'O',
+                OccupyType::Blocked => '@',
+                _ => '-',
+            });
+        }
+    }
+    ret
+}
+
+
+impl Rules {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn is_standard(&self) -> bool {
+        matches!(self.start, Start::Standard) && self.handicap == 0 && !self.misere && self.blocked.is_empty()
+            && self.shape == Shape::Square
+    }
+
+    //what the computer players play for
+    pub fn goal(&self) -> Goal {
+        if self.misere { Goal::FewestDiscs } else { Goal::MostDiscs }
+    }
+
+    //the position before the first move
+    pub fn field(&self) -> Field {
+        let mut f = match &self.start {
+            Start::Standard => {
+                let mut f = Field::new();
+                f.init();
+                f
+            }
+            Start::Parallel => {
+                let mut f = Field::new();
+                let middle = BOARD_SIZE / 2;
+                f.set_type(&Point::new(middle, middle + 1), OccupyType::Black);
+                f.set_type(&Point::new(middle + 1, middle + 1), OccupyType::Black);
+                f.set_type(&Point::new(middle, middle), OccupyType::White);
+                f.set_type(&Point::new(middle + 1, middle), OccupyType::White);
+                f
+            }
+            Start::Custom(f) => f.clone(),
+        };
+        f.set_shape(self.shape);
+        for (x, y) in HANDICAP_CORNERS.iter().take(self.handicap as usize) {
+            f.set_type(&Point::new(*x, *y), self.handicap_side);
+        }
+        for p in &self.blocked {
+            f.set_type(p, OccupyType::Blocked);
+        }
+        f
+    }
+
+    //(white, black) as `FieldAction::get_score_wb`
+    pub fn score(&self, f: &Field) -> (u8, u8) {
+        f.get_score_wb()
+    }
+
+    //the side with more points (fewer for misère), None for a draw
+    pub fn winner(&self, f: &Field) -> Option<OccupyType> {
+        let (w, b) = self.score(f);
+        let (w, b) = if self.misere { (b, w) } else { (w, b) };
+        if w > b {
+            Some(OccupyType::White)
+        } else if b > w {
+            Some(OccupyType::Black)
+        } else {
+            None
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<Rules, String> {
+        let mut rules = Rules::new();
+        for word in s.split(',').map(str::trim).filter(|w| !w.is_empty()) {
+            let (name, value) = word.split_once(':').unwrap_or((word, ""));
+            match (name.to_lowercase().as_str(), value) {
+                ("standard", "") => rules.start = Start::Standard,
+                ("parallel", "") => rules.start = Start::Parallel,
+                ("misere", "") | ("anti", "") => rules.misere = true,
+                ("custom", board) => {
+                    let f = parse_board(board).map_err(|e| format!("wrong custom board: {}", e))?;
+                    rules.start = Start::Custom(f);
+                }
+                ("blocked", cells) => {
+                    for cell in cells.split('+') {
+                        let p = Point::from_algebraic(cell)
+                            .ok_or_else(|| format!("wrong blocked cell '{}'", cell))?;
+                        if !rules.blocked.contains(&p) {
+                            rules.blocked.push(p);
+                        }
+                    }
+                }
+                ("shape", shape) => rules.shape = Shape::from_string(shape)?,
+                ("handicap", value) => {
+                    let (n, side) = value.split_once(':').unwrap_or((value, "#"));
+                    rules.handicap = n.parse().ok().filter(|n| (1..=MAX_HANDICAP).contains(n))
+                        .ok_or_else(|| format!("wrong handicap '{}', expected 1..{}", n, MAX_HANDICAP))?;
+                    rules.handicap_side = side_from_str(side).ok_or_else(|| format!("unknown color '{}'", side))?;
+                }
+                _ => return Err(format!("unknown rule '{}'", word)),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.start {
+            Start::Standard => write!(f, "standard")?,
+            Start::Parallel => write!(f, "parallel")?,
+            Start::Custom(board) => write!(f, "custom:{}", board_to_string(board))?,
+        }
+        if self.handicap > 0 {
+            write!(f, ",handicap:{}:{}", self.handicap, self.handicap_side)?;
+        }
+        if !self.blocked.is_empty() {
+            let cells: Vec<String> = self.blocked.iter().map(|p| p.to_algebraic()).collect();
+            write!(f, ",blocked:{}", cells.join("+"))?;
+        }
+        if self.shape != Shape::Square {
+            write!(f, ",shape:{}", self.shape)?;
+        }
+        if self.misere {
+            write!(f, ",misere")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Rules {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Rules {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        Rules::from_string(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+
+#[test]
+fn rules_start_test() {
+    let mut standard = Field::new();
+    standard.init();
+    assert_eq!(standard.serialize(), Rules::new().field().serialize());
+
+    let parallel = Rules::from_string("parallel").unwrap().field();
+    assert_eq!(OccupyType::Black, parallel.get_type(&Point::from_algebraic("d4").unwrap()));
+    assert_eq!(OccupyType::Black, parallel.get_type(&Point::from_algebraic("e4").unwrap()));
+    assert_eq!(OccupyType::White, parallel.get_type(&Point::from_algebraic("e5").unwrap()));
+    assert_eq!((2, 2), parallel.get_score_wb());
+    assert!(!parallel.get_list_of_moves(OccupyType::Black).is_empty());
+
+    let handicap = Rules::from_string("handicap:3:O").unwrap();
+    let f = handicap.field();
+    assert_eq!((5, 2), handicap.score(&f));
+    assert_eq!(OccupyType::White, f.get_type(&Point::new(1, 8)));
+    assert_eq!(OccupyType::Empty, f.get_type(&Point::new(1, 1)));
+    assert_eq!(Some(OccupyType::White), handicap.winner(&f));
+
+    let misere = Rules::from_string("handicap:3:O,anti").unwrap();
+    assert_eq!(Some(OccupyType::Black), misere.winner(&f));
+    assert_eq!(Goal::FewestDiscs, misere.goal());
+    assert!(!misere.is_standard());
+}
+
+#[test]
+fn rules_names_test() {
+    for s in ["standard", "parallel", "parallel,handicap:2:O", "standard,handicap:4:#", "standard,misere",
+              "standard,blocked:a1+d3"] {
+        assert_eq!(s, Rules::from_string(s).unwrap().to_string());
+    }
+    assert_eq!("standard,handicap:1:#", Rules::from_string("handicap:1").unwrap().to_string());
+    assert!(Rules::from_string("").unwrap().is_standard());
+
+    let custom = format!("custom:{}", "X".repeat(2) + &"O".repeat(2) + &"-".repeat(60));
+    let rules = Rules::from_string(&custom).unwrap();
+    assert_eq!(custom, rules.to_string());
+    assert_eq!((2, 2), rules.field().get_score_wb());
+
+    //a template with holes, the blocked cells of the rules are added to it
+    let template = format!("custom:{}", "@".repeat(8) + &"-".repeat(19) + "OX------XO" + &"-".repeat(27));
+    let rules = Rules::from_string(&(template + ",blocked:d3+E6")).unwrap();
+    let f = rules.field();
+    assert_eq!(OccupyType::Blocked, f.get_type(&Point::new(1, 8)));
+    assert_eq!(OccupyType::Blocked, f.get_type(&Point::from_algebraic("e6").unwrap()));
+    assert_eq!(2, f.get_list_of_moves(OccupyType::Black).len());
+    assert_eq!(rules.to_string(), Rules::from_string(&rules.to_string()).unwrap().to_string());
+
+    let octagon = Rules::from_string("shape:octagon,handicap:2").unwrap();
+    assert_eq!("standard,handicap:2:#,shape:octagon:2", octagon.to_string());
+    let f = octagon.field();
+    assert_eq!(Shape::Octagon(2), f.shape());
+    assert_eq!((2, 2), f.get_score_wb());
+    assert_eq!(4, f.get_list_of_moves(OccupyType::Black).len());
+    assert!(!octagon.is_standard());
+    assert_eq!(Shape::Rectangle(6, 8), Rules::from_string("shape:rect:6x8").unwrap().shape);
+
+    assert!(Rules::from_string("handicap:5").is_err());
+    assert!(Rules::from_string("shape:circle").is_err());
+    assert!(Rules::from_string("octagon").is_err());
+    assert!(Rules::from_string("custom:XO").is_err());
+}
@@ -0,0 +1,277 @@
+/*
+Saved game file, version 3. A text file of `key: value` lines, the board takes the 8 lines after `board:`
+
+    rustversi-save: 3
+    rules: standard
+    white: human
+    black: search:4
+    to-move: O
+    moves: (5,6) (6,6) pass (4,3)
+    board:
+    ........
+    ........
+    .....#..
+    ...O##..
+    ...#O...
+    ........
+    ........
+    ........
+
+  rustversi-save  -- format version, must be the first line; the older versions are read too: version 1 has
+                     no rules, version 3 has added the `@` cells and the `shape` of the rules
+  rules           -- the variant of `Rules::from_string`, optional (standard)
+  white, black    -- `human` or the engine of the computer player (see `Engine::from_string`)
+  to-move         -- `O` or `#`, the side to move next
+  moves           -- the move history from the start position of the rules, `pass` for a pass (may be empty)
+  board           -- row 8 first, `#` black, `O` white, `.` empty, `@` blocked
+
+Empty lines and lines starting with `;` are ignored. Unknown keys are an error, so a newer version
+must bump the version number.
+*/
+use std::fs;
+use std::fmt;
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::engine::Engine;
+use crate::rules::Rules;
+#[cfg(test)]
+use crate::shape::Shape;
+
+
+pub const SAVE_VERSION: u32 = 3;
+const HEADER: &str = "rustversi-save";
+
+
+//with the `serde` feature it is serialized as
+//{"field": {"rows": [...]}, "to_move": "white", "white": null, "black": "search:4", "moves": [{"x": 5, "y": 6}, null],
+// "rules": "standard"}
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedGame {
+    pub field: Field,
+    pub to_move: OccupyType,
+    //None for a human player
+    pub white: Option<Engine>,
+    pub black: Option<Engine>,
+    //None for a pass
+    pub moves: Vec<Option<Point>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Rules,
+}
+
+
+fn player_to_string(player: &Option<Engine>) -> String {
+    match player {
+        Some(engine) => engine.to_string(),
+        None => "human".to_string(),
+    }
+}
+
+fn player_from_string(s: &str) -> Result<Option<Engine>, String> {
+    if s == "human" {
+        return Ok(None);
+    }
+    match Engine::from_string(s) {
+        Some(engine) => Ok(Some(engine)),
+        None => Err(format!("unknown player '{}'", s)),
+    }
+}
+
+
+impl SavedGame {
+    pub fn from_string(s: &str) -> Result<SavedGame, String> {
+        let mut lines = s.lines().enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'));
+
+        match lines.next() {
+            Some((_, line)) if line.starts_with(HEADER) => {
+                let version = line[HEADER.len()..].trim_start_matches(':').trim();
+                if !(1..=SAVE_VERSION).any(|v| version == v.to_string()) {
+                    return Err(format!("unsupported save version '{}'", version));
+                }
+            }
+            _ => return Err("not a rustversi saved game".to_string()),
+        }
+
+        let mut rules = Rules::new();
+        let mut white = None;
+        let mut black = None;
+        let mut to_move = None;
+        let mut moves = vec![];
+        let mut board: Option<Field> = None;
+        while let Some((n, line)) = lines.next() {
+            let (key, value) = match line.find(':') {
+                Some(i) => (&line[..i], line[i + 1..].trim()),
+                None => return Err(format!("line {}: expected `key: value`", n)),
+            };
+            let err = |msg: String| format!("line {}: {}", n, msg);
+            match key {
+                "rules" => rules = Rules::from_string(value).map_err(err)?,
+                "white" => white = Some(player_from_string(value).map_err(err)?),
+                "black" => black = Some(player_from_string(value).map_err(err)?),
+                "to-move" => {
+                    match OccupyType::side_from_string(value) {
+                        Some(bw) => to_move = Some(bw),
+                        None => return Err(err(format!("wrong side to move '{}'", value))),
+                    }
+                }
+                "moves" => {
+                    for m in value.split_ascii_whitespace() {
+                        if m == "pass" {
+                            moves.push(None);
+                            continue;
+                        }
+                        let p = Point::from_string(m).ok_or_else(|| err(format!("wrong move '{}'", m)))?;
+                        moves.push(Some(p));
+                    }
+                }
+                "board" => {
+                    //rows are joined into the `FieldAction::serialize` string
+                    let mut cells = String::new();
+                    for _ in 0..BOARD_SIZE {
+                        let (n, row) = lines.next().ok_or_else(|| "the board is too short".to_string())?;
+                        if row.chars().count() != BOARD_SIZE as usize {
+                            return Err(format!("line {}: a board row must have {} cells", n, BOARD_SIZE));
+                        }
+                        for c in row.chars() {
+                            match c {
+                                '#' | 'O' | '@' => cells.push(c),
+                                '.' => cells.push(' '),
+                                _ => return Err(format!("line {}: unexpected symbol '{}'", n, c)),
+                            }
+                        }
+                    }
+                    let mut f = Field::new();
+                    f.deserialize(&cells);
+                    board = Some(f);
+                }
+                _ => return Err(err(format!("unknown key '{}'", key))),
+            }
+        }
+
+        //the cells outside the shape are saved as blocked ones
+        let mut field = board.ok_or("the board is missing")?;
+        field.set_shape(rules.shape);
+        Ok(SavedGame {
+            field,
+            to_move: to_move.ok_or("the side to move is missing")?,
+            white: white.ok_or("the white player is missing")?,
+            black: black.ok_or("the black player is missing")?,
+            moves,
+            rules,
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|e| format!("cannot write {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<SavedGame, String> {
+        let s = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        SavedGame::from_string(&s)
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut ret = format!("{}: {}\n", HEADER, SAVE_VERSION);
+        ret.push_str(&format!("rules: {}\n", self.rules));
+        ret.push_str(&format!("white: {}\n", player_to_string(&self.white)));
+        ret.push_str(&format!("black: {}\n", player_to_string(&self.black)));
+        ret.push_str(&format!("to-move: {}\n", self.to_move));
+        let moves: Vec<String> = self.moves.iter().map(|m| match m {
+            Some(p) => p.to_string(),
+            None => "pass".to_string(),
+        }).collect();
+        ret.push_str(format!("moves: {}", moves.join(" ")).trim_end());
+        ret.push_str("\nboard:\n");
+        for y in (1..=BOARD_SIZE).rev() {
+            for x in 1..=BOARD_SIZE {
+                ret.push(match self.field.get_type(&Point::new(x, y)) {
+                    OccupyType::Black => '#',
+                    OccupyType::White => 'O',
+                    OccupyType::Blocked => '@',
+                    _ => '.',
+                });
+            }
+            ret.push('\n');
+        }
+        write!(f, "{}", ret)
+    }
+}
+
+
+#[test]
+fn savegame_round_trip_test() {
+    let mut f = Field::new();
+    f.init();
+    f.move_in_game(&Point::new(5, 6), OccupyType::White);
+    let game = SavedGame {
+        field: f,
+        to_move: OccupyType::Black,
+        white: None,
+        black: Some(Engine::Search(3)),
+        moves: vec![Some(Point::new(5, 6))],
+        rules: Rules::from_string("parallel,handicap:1").unwrap(),
+    };
+    let s = game.to_string();
+    let loaded = SavedGame::from_string(&s).unwrap();
+    assert_eq!(game.field.serialize(), loaded.field.serialize());
+    assert_eq!(OccupyType::Black, loaded.to_move);
+    assert_eq!(None, loaded.white);
+    assert_eq!(Some(Engine::Search(3)), loaded.black);
+    assert_eq!(game.moves, loaded.moves);
+    assert_eq!("parallel,handicap:1:#", loaded.rules.to_string());
+    assert_eq!(s, loaded.to_string());
+
+    //version 1 has no rules
+    let old = s.replace("rustversi-save: 3", "rustversi-save: 1").replace("rules: parallel,handicap:1:#\n", "");
+    assert!(SavedGame::from_string(&old).unwrap().rules.is_standard());
+
+    //version 2 has no shape
+    let v2 = s.replace("rustversi-save: 3", "rustversi-save: 2");
+    let loaded = SavedGame::from_string(&v2).unwrap();
+    assert_eq!(Shape::Square, loaded.rules.shape);
+    assert_eq!(game.field.serialize(), loaded.field.serialize());
+
+    let rules = Rules::from_string("shape:octagon").unwrap();
+    let shaped = SavedGame { field: rules.field(), rules, ..game };
+    let loaded = SavedGame::from_string(&shaped.to_string()).unwrap();
+    assert_eq!(Shape::Octagon(2), loaded.rules.shape);
+    assert_eq!(shaped.field.serialize(), loaded.field.serialize());
+}
+
+#[test]
+fn savegame_errors_test() {
+    assert!(SavedGame::from_string("hello").is_err());
+    assert!(SavedGame::from_string("rustversi-save: 4\n").is_err());
+    let err = SavedGame::from_string("rustversi-save: 1\nwhite: genius\n").unwrap_err();
+    assert_eq!("line 2: unknown player 'genius'", err);
+    assert!(SavedGame::from_string("rustversi-save: 1\nwhite: human\n").unwrap_err().contains("missing"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn savegame_serde_test() {
+    let mut f = Field::new();
+    f.init();
+    let game = SavedGame {
+        field: f,
+        to_move: OccupyType::White,
+        white: Some(Engine::Corner),
+        black: None,
+        moves: vec![None],
+        rules: Rules::new(),
+    };
+    let json = serde_json::to_value(&game).unwrap();
+    assert_eq!("white", json["to_move"]);
+    assert_eq!("corner", json["white"]);
+    assert!(json["black"].is_null());
+    assert!(json["moves"][0].is_null());
+    assert_eq!("standard", json["rules"]);
+    let loaded: SavedGame = serde_json::from_value(json).unwrap();
+    assert_eq!(game.to_string(), loaded.to_string());
+}
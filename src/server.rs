@@ -0,0 +1,504 @@
+/*
+A game server over WebSocket (the `server` feature), run by the `rustversi-server` binary. The games
+are rooms with a name: the first two players who join take the white and the black seat, the others
+watch as spectators. The server is authoritative: it checks every move with `Field::is_valid_move`,
+passes for a side without a legal move and ends the game.
+
+Every message is a JSON text with "type":
+
+client -> server
+  {"type": "join", "game": "office", "name": "ann"}   -- "spectator": true to watch, "rules": "parallel"
+                                                         for a new game (`rules::Rules`, standard by default),
+                                                         an existing game must have the same rules
+  {"type": "move", "x": 5, "y": 6}
+  {"type": "resign"}
+  {"type": "chat", "text": "good luck"}
+  {"type": "sync"}                                    -- asks for the state
+
+server -> client
+  {"type": "joined", "game": "office", "color": "white"}  -- null for a spectator
+  {"type": "state", "game": "office", "board": {"rows": [...]}, "to_move": "white", "white": "ann",
+   "black": "bob", "spectators": 0, "moves": [{"x": 5, "y": 6}, null], "score": {"white": 4, "black": 1},
+   "over": false, "winner": null, "resigned": null, "rules": "standard"}
+      -- to everybody in the game after every change; white moves first, null in moves is a pass,
+         to_move is null while a seat is free and after the end
+  {"type": "chat", "from": "ann", "text": "good luck"}
+  {"type": "error", "message": "..."}                      -- to the sender only
+
+A player who leaves frees the seat, whoever joins next takes it and the game goes on.
+*/
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender};
+use std::time::Duration;
+
+use tungstenite::Message;
+
+use crate::point::Point;
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::game::possible_movement;
+use crate::rules::Rules;
+
+
+//how long a connection waits for a message before it sends what the others have broadcast
+const POLL: Duration = Duration::from_millis(20);
+
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ClientMessage {
+    Join {
+        game: String,
+        name: String,
+        #[serde(default)]
+        spectator: bool,
+        #[serde(default)]
+        rules: Option<Rules>,
+    },
+    Move { x: u8, y: u8 },
+    Resign,
+    Chat { text: String },
+    Sync,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Score {
+    pub white: u8,
+    pub black: u8,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GameState {
+    pub game: String,
+    pub board: Field,
+    pub to_move: Option<OccupyType>,
+    pub white: Option<String>,
+    pub black: Option<String>,
+    pub spectators: usize,
+    pub moves: Vec<Option<Point>>,
+    pub score: Score,
+    pub over: bool,
+    //None for a draw
+    pub winner: Option<OccupyType>,
+    pub resigned: Option<OccupyType>,
+    pub rules: Rules,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerMessage {
+    Joined { game: String, color: Option<OccupyType> },
+    State(Box<GameState>),
+    Chat { from: String, text: String },
+    Error { message: String },
+}
+
+impl ServerMessage {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a server message is always serializable")
+    }
+}
+
+
+//a connection in a game
+struct Member {
+    id: usize,
+    name: String,
+    tx: Sender<String>,
+}
+
+struct Room {
+    rules: Rules,
+    field: Field,
+    to_move: OccupyType,
+    moves: Vec<Option<Point>>,
+    white: Option<Member>,
+    black: Option<Member>,
+    spectators: Vec<Member>,
+    over: bool,
+    resigned: Option<OccupyType>,
+}
+
+impl Room {
+    fn new(rules: Rules) -> Room {
+        Room { field: rules.field(), rules, to_move: OccupyType::White, moves: vec![], white: None, black: None,
+               spectators: vec![], over: false, resigned: None }
+    }
+
+    fn seat(&self, bw: OccupyType) -> &Option<Member> {
+        if bw == OccupyType::White { &self.white } else { &self.black }
+    }
+
+    fn members(&self) -> impl Iterator<Item = &Member> {
+        self.white.iter().chain(self.black.iter()).chain(self.spectators.iter())
+    }
+
+    //the color of the player, None for a spectator
+    fn color(&self, id: usize) -> Option<OccupyType> {
+        [OccupyType::White, OccupyType::Black].iter().copied()
+            .find(|bw| self.seat(*bw).as_ref().is_some_and(|m| m.id == id))
+    }
+
+    fn member(&self, id: usize) -> Option<&Member> {
+        self.members().find(|m| m.id == id)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.members().next().is_none()
+    }
+
+    fn broadcast(&self, message: &ServerMessage) {
+        let json = message.to_json();
+        for m in self.members() {
+            //a closed connection is removed by its own thread
+            let _ = m.tx.send(json.clone());
+        }
+    }
+
+    fn state(&self, game: &str) -> GameState {
+        let (white, black) = self.rules.score(&self.field);
+        let name = |bw: OccupyType| self.seat(bw).as_ref().map(|m| m.name.clone());
+        let playing = !self.over && self.white.is_some() && self.black.is_some();
+        let winner = match self.resigned {
+            Some(bw) => Some(OccupyType::get_opposite_type(bw)),
+            None if self.over => self.rules.winner(&self.field),
+            None => None,
+        };
+        GameState {
+            game: game.to_string(),
+            board: self.field.clone(),
+            to_move: if playing { Some(self.to_move) } else { None },
+            white: name(OccupyType::White),
+            black: name(OccupyType::Black),
+            spectators: self.spectators.len(),
+            moves: self.moves.clone(),
+            score: Score { white, black },
+            over: self.over,
+            winner,
+            resigned: self.resigned,
+            rules: self.rules.clone(),
+        }
+    }
+
+    fn play(&mut self, bw: OccupyType, p: &Point) -> Result<(), String> {
+        if self.over {
+            return Err("the game is over".to_string());
+        }
+        if self.white.is_none() || self.black.is_none() {
+            return Err("waiting for the opponent".to_string());
+        }
+        if bw != self.to_move {
+            return Err(format!("it is the move of {}", self.to_move));
+        }
+        let (valid, _) = self.field.is_valid_move(p, bw);
+        if !valid {
+            return Err(format!("{} is not a valid move for {}", p, bw));
+        }
+        self.field.move_in_game(p, bw);
+        self.moves.push(Some(*p));
+        self.to_move = OccupyType::get_opposite_type(bw);
+        //the passes are made by the server
+        if !possible_movement(self.to_move, &self.field) {
+            if possible_movement(bw, &self.field) {
+                self.moves.push(None);
+                self.to_move = bw;
+            } else {
+                self.over = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+type Games = Arc<Mutex<HashMap<String, Room>>>;
+
+//what a connection knows about itself
+struct Connection {
+    id: usize,
+    tx: Sender<String>,
+    game: Option<String>,
+}
+
+impl Connection {
+    fn reply(&self, message: ServerMessage) {
+        let _ = self.tx.send(message.to_json());
+    }
+
+    fn handle(&mut self, games: &Games, text: &str) -> Result<(), String> {
+        let message: ClientMessage = serde_json::from_str(text).map_err(|e| format!("wrong message: {}", e))?;
+        let mut games = games.lock().expect("the games lock is poisoned");
+        if let ClientMessage::Join { game, name, spectator, rules } = message {
+            if self.game.is_some() {
+                return Err("already in a game".to_string());
+            }
+            //the rules of a game cannot change once it is there
+            if let (Some(room), Some(rules)) = (games.get(&game), &rules) {
+                if rules.to_string() != room.rules.to_string() {
+                    return Err(format!("the game '{}' is played with the rules '{}'", game, room.rules));
+                }
+            }
+            let room = games.entry(game.clone()).or_insert_with(|| Room::new(rules.unwrap_or_default()));
+            let member = Member { id: self.id, name, tx: self.tx.clone() };
+            let color = if spectator {
+                room.spectators.push(member);
+                None
+            } else if room.white.is_none() {
+                room.white = Some(member);
+                Some(OccupyType::White)
+            } else if room.black.is_none() {
+                room.black = Some(member);
+                Some(OccupyType::Black)
+            } else {
+                return Err(format!("the game '{}' has two players, join as a spectator", game));
+            };
+            self.reply(ServerMessage::Joined { game: game.clone(), color });
+            room.broadcast(&ServerMessage::State(Box::new(room.state(&game))));
+            self.game = Some(game);
+            return Ok(());
+        }
+
+        let game = self.game.clone().ok_or("join a game first")?;
+        let room = games.get_mut(&game).expect("the room of a member exists");
+        match message {
+            ClientMessage::Move { x, y } => {
+                let bw = room.color(self.id).ok_or("spectators cannot move")?;
+                if !Point::check_point(x as i8, y as i8) {
+                    return Err(format!("({},{}) is out of the board", x, y));
+                }
+                room.play(bw, &Point::new(x, y))?;
+                room.broadcast(&ServerMessage::State(Box::new(room.state(&game))));
+            }
+            ClientMessage::Resign => {
+                let bw = room.color(self.id).ok_or("spectators cannot resign")?;
+                if room.over {
+                    return Err("the game is over".to_string());
+                }
+                room.over = true;
+                room.resigned = Some(bw);
+                room.broadcast(&ServerMessage::State(Box::new(room.state(&game))));
+            }
+            ClientMessage::Chat { text } => {
+                let from = room.member(self.id).map(|m| m.name.clone()).unwrap_or_default();
+                room.broadcast(&ServerMessage::Chat { from, text });
+            }
+            ClientMessage::Sync => self.reply(ServerMessage::State(Box::new(room.state(&game)))),
+            ClientMessage::Join { .. } => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self, games: &Games) {
+        let game = match self.game.take() {
+            Some(game) => game,
+            None => return,
+        };
+        let mut games = games.lock().expect("the games lock is poisoned");
+        let room = match games.get_mut(&game) {
+            Some(room) => room,
+            None => return,
+        };
+        let id = self.id;
+        if room.white.as_ref().is_some_and(|m| m.id == id) {
+            room.white = None;
+        }
+        if room.black.as_ref().is_some_and(|m| m.id == id) {
+            room.black = None;
+        }
+        room.spectators.retain(|m| m.id != id);
+        if room.is_empty() {
+            games.remove(&game);
+        } else {
+            room.broadcast(&ServerMessage::State(Box::new(room.state(&game))));
+        }
+    }
+}
+
+
+fn serve(stream: TcpStream, games: Games, id: usize) {
+    let mut ws = match tungstenite::accept(stream) {
+        Ok(ws) => ws,
+        Err(_) => return,
+    };
+    if ws.get_mut().set_read_timeout(Some(POLL)).is_err() {
+        return;
+    }
+    let (tx, rx) = mpsc::channel();
+    let mut connection = Connection { id, tx, game: None };
+    'connection: loop {
+        match ws.read() {
+            Ok(Message::Text(text)) => {
+                if let Err(message) = connection.handle(&games, &text) {
+                    connection.reply(ServerMessage::Error { message });
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {}
+            Err(_) => break,
+        }
+        while let Ok(json) = rx.try_recv() {
+            if ws.send(Message::Text(json)).is_err() {
+                break 'connection;
+            }
+        }
+    }
+    connection.leave(&games);
+}
+
+
+pub struct Server {
+    listener: TcpListener,
+    games: Games,
+}
+
+impl Server {
+    //"0.0.0.0:9001", port 0 for any free one
+    pub fn bind(addr: &str) -> io::Result<Server> {
+        Ok(Server { listener: TcpListener::bind(addr)?, games: Default::default() })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    //serves the connections, a thread for each one; returns only on an error of the listener
+    pub fn run(self) -> io::Result<()> {
+        for (id, stream) in self.listener.incoming().enumerate() {
+            let stream = stream?;
+            let games = self.games.clone();
+            std::thread::spawn(move || serve(stream, games, id));
+        }
+        Ok(())
+    }
+}
+
+
+#[test]
+fn server_room_test() {
+    let (tx, _rx) = mpsc::channel();
+    let member = |id: usize| Member { id, name: format!("p{}", id), tx: tx.clone() };
+    let mut room = Room::new(Rules::new());
+    room.white = Some(member(1));
+    assert!(room.play(OccupyType::White, &Point::new(5, 6)).is_err());
+    room.black = Some(member(2));
+    assert_eq!(Some(OccupyType::Black), room.color(2));
+    assert!(room.play(OccupyType::Black, &Point::new(5, 6)).is_err());
+    assert!(room.play(OccupyType::White, &Point::new(1, 1)).is_err());
+    room.play(OccupyType::White, &Point::new(5, 6)).unwrap();
+    let state = room.state("g");
+    assert_eq!(Some(OccupyType::Black), state.to_move);
+    assert_eq!((4, 1), (state.score.white, state.score.black));
+
+    //black has no move left: the game is over at once
+    room.play(OccupyType::Black, &Point::new(6, 4)).unwrap();
+    room.field = Field::new();
+    room.field.set_type(&Point::new(1, 1), OccupyType::White);
+    room.field.set_type(&Point::new(1, 2), OccupyType::Black);
+    room.field.set_type(&Point::new(3, 3), OccupyType::White);
+    room.to_move = OccupyType::White;
+    room.play(OccupyType::White, &Point::new(1, 3)).unwrap();
+    let state = room.state("g");
+    assert!(state.over);
+    assert_eq!(Some(OccupyType::White), state.winner);
+    assert_eq!(None, state.to_move);
+}
+
+#[test]
+fn server_messages_test() {
+    let join: ClientMessage = serde_json::from_str(r#"{"type": "join", "game": "g", "name": "ann"}"#).unwrap();
+    assert!(matches!(join, ClientMessage::Join { spectator: false, rules: None, .. }));
+    let join: ClientMessage = serde_json::from_str(r#"{"type": "join", "game": "g", "name": "ann", "rules": "parallel"}"#).unwrap();
+    assert!(matches!(join, ClientMessage::Join { rules: Some(_), .. }));
+    assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "move", "x": 5}"#).is_err());
+    assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "join", "game": "g", "name": "a", "rules": "x"}"#).is_err());
+    assert_eq!(r#"{"type":"error","message":"no"}"#, ServerMessage::Error { message: "no".to_string() }.to_json());
+}
+
+#[cfg(test)]
+fn server_client(addr: SocketAddr) -> tungstenite::WebSocket<tungstenite::stream::MaybeTlsStream<TcpStream>> {
+    let (ws, _) = tungstenite::connect(format!("ws://{}", addr)).unwrap();
+    ws
+}
+
+//the next message of the type, skipping the others
+#[cfg(test)]
+fn server_expect(ws: &mut tungstenite::WebSocket<tungstenite::stream::MaybeTlsStream<TcpStream>>, kind: &str) -> serde_json::Value {
+    loop {
+        if let Message::Text(text) = ws.read().unwrap() {
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            if value["type"] == kind {
+                return value;
+            }
+        }
+    }
+}
+
+#[test]
+fn server_localhost_test() {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    std::thread::spawn(move || server.run());
+    let send = |ws: &mut tungstenite::WebSocket<_>, json: &str| ws.send(Message::Text(json.to_string())).unwrap();
+
+    let mut ann = server_client(addr);
+    send(&mut ann, r#"{"type": "join", "game": "g", "name": "ann"}"#);
+    assert_eq!("white", server_expect(&mut ann, "joined")["color"]);
+    send(&mut ann, r#"{"type": "move", "x": 5, "y": 6}"#);
+    assert_eq!("waiting for the opponent", server_expect(&mut ann, "error")["message"]);
+
+    let mut bob = server_client(addr);
+    send(&mut bob, r#"{"type": "join", "game": "g", "name": "bob"}"#);
+    assert_eq!("black", server_expect(&mut bob, "joined")["color"]);
+    let mut eve = server_client(addr);
+    send(&mut eve, r#"{"type": "join", "game": "g", "name": "eve", "spectator": true}"#);
+    assert!(server_expect(&mut eve, "joined")["color"].is_null());
+    let state = server_expect(&mut eve, "state");
+    assert_eq!(("ann", "bob", 1), (state["white"].as_str().unwrap(), state["black"].as_str().unwrap(), state["spectators"].as_u64().unwrap()));
+    assert_eq!("white", state["to_move"]);
+
+    send(&mut bob, r#"{"type": "move", "x": 5, "y": 6}"#);
+    assert!(server_expect(&mut bob, "error")["message"].as_str().unwrap().starts_with("it is the move of"));
+    send(&mut eve, r#"{"type": "move", "x": 5, "y": 6}"#);
+    assert_eq!("spectators cannot move", server_expect(&mut eve, "error")["message"]);
+    send(&mut ann, r#"{"type": "move", "x": 1, "y": 1}"#);
+    server_expect(&mut ann, "error");
+    send(&mut ann, r#"{"type": "move", "x": 5, "y": 6}"#);
+    //the spectator sees the move
+    let state = loop {
+        let state = server_expect(&mut eve, "state");
+        if state["moves"].as_array().unwrap().len() == 1 {
+            break state;
+        }
+    };
+    assert_eq!(serde_json::json!({"x": 5, "y": 6}), state["moves"][0]);
+    assert_eq!(4, state["score"]["white"]);
+    assert_eq!("black", state["to_move"]);
+
+    send(&mut eve, r#"{"type": "chat", "text": "nice"}"#);
+    let chat = server_expect(&mut bob, "chat");
+    assert_eq!(("eve", "nice"), (chat["from"].as_str().unwrap(), chat["text"].as_str().unwrap()));
+
+    let mut joe = server_client(addr);
+    send(&mut joe, r#"{"type": "join", "game": "g", "name": "joe", "spectator": true, "rules": "parallel"}"#);
+    assert_eq!("the game 'g' is played with the rules 'standard'", server_expect(&mut joe, "error")["message"]);
+    send(&mut joe, r#"{"type": "join", "game": "g", "name": "joe", "spectator": true, "rules": "standard"}"#);
+    assert!(server_expect(&mut joe, "joined")["color"].is_null());
+
+    send(&mut bob, r#"{"type": "resign"}"#);
+    send(&mut eve, r#"{"type": "sync"}"#);
+    let state = loop {
+        let state = server_expect(&mut eve, "state");
+        if state["over"] == true {
+            break state;
+        }
+    };
+    assert_eq!(("black", "white"), (state["resigned"].as_str().unwrap(), state["winner"].as_str().unwrap()));
+    send(&mut ann, r#"{"type": "move", "x": 6, "y": 4}"#);
+    assert_eq!("the game is over", server_expect(&mut ann, "error")["message"]);
+    send(&mut ann, "hello");
+    assert!(server_expect(&mut ann, "error")["message"].as_str().unwrap().starts_with("wrong message"));
+}
@@ -0,0 +1,202 @@
+/*
+Board shapes: masks on the `BOARD_SIZE` x `BOARD_SIZE` grid, the cells outside the shape are not a part of the board.
+`Field::with_shape` builds a field of the shape; outside cells read as `OccupyType::Blocked`, so no disc is placed
+there and they stop the flipping lines, and they are serialized as `@`.
+
+  square          -- the whole grid, the default
+  rect:<w>x<h>    -- a w x h rectangle in the middle of the grid (2..8)
+  octagon[:<n>]   -- the grid with the corners clipped, n cells along the edges (1..3, default 2)
+  mask:<cells>    -- any shape, 64 cells of `1` (on the board) and `0`, row 8 first
+
+The corners of a shape are its stable cells: the cells which have a neighbour off the board on every line
+(`Shape::is_corner`); for the octagon they are the ends of the clipped edges.
+*/
+use core::fmt;
+
+use crate::point::{Point, BOARD_SIZE};
+
+
+pub const DEFAULT_OCTAGON_CLIP: u8 = 2;
+const LINES: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Shape {
+    #[default]
+    Square,
+    //width, height
+    Rectangle(u8, u8),
+    //the number of the clipped cells along each edge at a corner
+    Octagon(u8),
+    //bit order of `Field::bitboards`: bit 0 is (1,8)
+    Mask(u64),
+}
+
+
+fn bit(x: u8, y: u8) -> u64 {
+    1u64 << ((BOARD_SIZE - y) as u32 * BOARD_SIZE as u32 + x as u32 - 1)
+}
+
+//the first cell of a centered range of n cells
+fn centered(n: u8) -> u8 {
+    (BOARD_SIZE - n) / 2 + 1
+}
+
+
+impl Shape {
+    pub fn mask(&self) -> u64 {
+        match *self {
+            Shape::Square => u64::MAX,
+            Shape::Mask(mask) => mask,
+            _ => {
+                let mut mask = 0;
+                for y in 1..=BOARD_SIZE {
+                    for x in 1..=BOARD_SIZE {
+                        if self.contains_xy(x, y) {
+                            mask |= bit(x, y);
+                        }
+                    }
+                }
+                mask
+            }
+        }
+    }
+
+    fn contains_xy(&self, x: u8, y: u8) -> bool {
+        match *self {
+            Shape::Square => true,
+            Shape::Rectangle(w, h) => {
+                let (x0, y0) = (centered(w), centered(h));
+                x >= x0 && x < x0 + w && y >= y0 && y < y0 + h
+            }
+            Shape::Octagon(clip) => {
+                //the distance from the nearest corner along the two edges
+                let dx = (x - 1).min(BOARD_SIZE - x);
+                let dy = (y - 1).min(BOARD_SIZE - y);
+                dx + dy >= clip
+            }
+            Shape::Mask(mask) => mask & bit(x, y) != 0,
+        }
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.contains_xy(p.x(), p.y())
+    }
+
+    //`Point::check_point` for the shape
+    pub fn check_point(&self, x: i8, y: i8) -> bool {
+        Point::check_point(x, y) && self.contains_xy(x as u8, y as u8)
+    }
+
+    pub fn cells(&self) -> Vec<Point> {
+        let mut ret = vec![];
+        for y in (1..=BOARD_SIZE).rev() {
+            for x in 1..=BOARD_SIZE {
+                if self.contains_xy(x, y) {
+                    ret.push(Point::new(x, y));
+                }
+            }
+        }
+        ret
+    }
+
+    //a cell of the shape which can never be flipped
+    pub fn is_corner(&self, p: &Point) -> bool {
+        let (x, y) = (p.x() as i8, p.y() as i8);
+        self.contains(p) && LINES.iter().all(|(dx, dy)| {
+            !self.check_point(x + dx, y + dy) || !self.check_point(x - dx, y - dy)
+        })
+    }
+
+    pub fn from_string(s: &str) -> Result<Shape, String> {
+        let s = s.trim();
+        let (name, value) = s.split_once(':').unwrap_or((s, ""));
+        let shape = match (name.to_lowercase().as_str(), value) {
+            ("square", "") => Shape::Square,
+            ("octagon", "") => Shape::Octagon(DEFAULT_OCTAGON_CLIP),
+            ("octagon", n) => {
+                let clip = n.parse().ok().filter(|n| (1..=3).contains(n))
+                    .ok_or_else(|| format!("wrong octagon clip '{}', expected 1..3", n))?;
+                Shape::Octagon(clip)
+            }
+            ("rect", size) => {
+                let wrong = || format!("wrong rectangle '{}', expected <w>x<h> of 2..{}", size, BOARD_SIZE);
+                let (w, h) = size.split_once(['x', 'X']).ok_or_else(wrong)?;
+                let side = |n: &str| n.parse::<u8>().ok().filter(|n| (2..=BOARD_SIZE).contains(n));
+                Shape::Rectangle(side(w).ok_or_else(wrong)?, side(h).ok_or_else(wrong)?)
+            }
+            ("mask", cells) => {
+                if cells.chars().count() != (BOARD_SIZE * BOARD_SIZE) as usize {
+                    return Err(format!("a mask must have {} cells", BOARD_SIZE * BOARD_SIZE));
+                }
+                let mut mask = 0;
+                for (i, c) in cells.chars().enumerate() {
+                    match c {
+                        '1' => mask |= 1u64 << i,
+                        '0' => {}
+                        _ => return Err(format!("unexpected symbol '{}' in the mask", c)),
+                    }
+                }
+                Shape::Mask(mask)
+            }
+            _ => return Err(format!("unknown shape '{}'", s)),
+        };
+        Ok(shape)
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shape::Square => write!(f, "square"),
+            Shape::Rectangle(w, h) => write!(f, "rect:{}x{}", w, h),
+            Shape::Octagon(clip) => write!(f, "octagon:{}", clip),
+            Shape::Mask(mask) => {
+                let cells: String = (0..BOARD_SIZE * BOARD_SIZE).map(|i| if mask & (1u64 << i) != 0 { '1' } else { '0' }).collect();
+                write!(f, "mask:{}", cells)
+            }
+        }
+    }
+}
+
+
+#[test]
+fn shape_cells_test() {
+    assert_eq!(64, Shape::Square.cells().len());
+    assert_eq!(52, Shape::Octagon(2).cells().len());
+    assert!(!Shape::Octagon(2).contains(&Point::new(2, 1)));
+    assert!(Shape::Octagon(2).contains(&Point::new(3, 1)));
+
+    let rect = Shape::from_string("rect:6x4").unwrap();
+    assert_eq!(24, rect.cells().len());
+    assert!(rect.check_point(2, 3) && rect.check_point(7, 6));
+    assert!(!rect.check_point(1, 3) && !rect.check_point(2, 7) && !rect.check_point(0, 4));
+    assert_eq!(rect.mask(), Shape::Mask(rect.mask()).mask());
+}
+
+#[test]
+fn shape_corners_test() {
+    let corners = |s: Shape| s.cells().into_iter().filter(|p| s.is_corner(p)).collect::<Vec<_>>();
+    assert_eq!(vec![Point::new(1, 8), Point::new(8, 8), Point::new(1, 1), Point::new(8, 1)], corners(Shape::Square));
+    //the ends of the clipped edges
+    let octagon = corners(Shape::Octagon(2));
+    assert_eq!(8, octagon.len());
+    assert!(octagon.contains(&Point::new(3, 1)) && octagon.contains(&Point::new(1, 3)));
+    assert!(!Shape::Octagon(2).is_corner(&Point::new(2, 2)));
+    assert!(Shape::Rectangle(6, 6).is_corner(&Point::new(2, 7)));
+}
+
+#[test]
+fn shape_names_test() {
+    for s in ["square", "rect:6x8", "octagon:1", "octagon:3"] {
+        assert_eq!(s, Shape::from_string(s).unwrap().to_string());
+    }
+    assert_eq!(Shape::Octagon(2), Shape::from_string("octagon").unwrap());
+    let mask = Shape::Mask(Shape::Octagon(3).mask()).to_string();
+    assert!(mask.starts_with("mask:00011000"));
+    assert_eq!(Shape::Octagon(3).mask(), Shape::from_string(&mask).unwrap().mask());
+    assert!(Shape::from_string("rect:9x2").is_err());
+    assert!(Shape::from_string("octagon:4").is_err());
+    assert!(Shape::from_string("mask:101").is_err());
+    assert!(Shape::from_string("circle").is_err());
+}
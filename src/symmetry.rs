@@ -0,0 +1,215 @@
+/*
+The 8 symmetries of the board (the dihedral group D4). A position and its transforms are equivalent,
+`Field::canonical` picks one representative so books and caches can store it once:
+
+    let (canonical, t) = field.canonical();
+    //a move stored for `canonical` is played on the real board at
+    let real = t.inverse().apply(&book_move);
+
+The rotations are clockwise as the board is printed (row 8 on the top).
+*/
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::Field;
+use crate::shape::Shape;
+#[cfg(test)]
+use crate::field::FieldAction;
+
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    //left-right mirror
+    FlipHorizontal,
+    //top-bottom mirror
+    FlipVertical,
+    //mirror on the a1-h8 diagonal, (1,8)-(8,1)
+    FlipDiagonal,
+    //mirror on the a8-h1 diagonal, (1,1)-(8,8)
+    FlipAntiDiagonal,
+}
+
+pub const TRANSFORMS: [Transform; 8] = [
+    Transform::Identity,
+    Transform::Rotate90,
+    Transform::Rotate180,
+    Transform::Rotate270,
+    Transform::FlipHorizontal,
+    Transform::FlipVertical,
+    Transform::FlipDiagonal,
+    Transform::FlipAntiDiagonal,
+];
+
+
+impl Transform {
+    pub fn apply(&self, p: &Point) -> Point {
+        let n = BOARD_SIZE + 1;
+        let (x, y) = (p.x(), p.y());
+        let (x, y) = match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (y, n - x),
+            Transform::Rotate180 => (n - x, n - y),
+            Transform::Rotate270 => (n - y, x),
+            Transform::FlipHorizontal => (n - x, y),
+            Transform::FlipVertical => (x, n - y),
+            Transform::FlipDiagonal => (n - y, n - x),
+            Transform::FlipAntiDiagonal => (y, x),
+        };
+        Point::new(x, y)
+    }
+
+    pub fn inverse(&self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            t => *t,
+        }
+    }
+}
+
+//the point of a board transformed with `from` on the same board transformed with `to`
+pub fn map_point(p: &Point, from: Transform, to: Transform) -> Point {
+    to.apply(&from.inverse().apply(p))
+}
+
+
+//splitmix64 finalizer, stable between builds unlike `DefaultHasher`
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+
+impl Field {
+    //(black, white) bitboards, bit 0 is (1,8) and bit 63 is (8,1) as in `FieldAction::serialize`
+    pub fn bitboards(&self) -> (u64, u64) {
+        (self.bitboard(OccupyType::Black), self.bitboard(OccupyType::White))
+    }
+
+    //the cells of the type, in the bit order of `bitboards`
+    pub fn bitboard(&self, t: OccupyType) -> u64 {
+        let mut ret = 0;
+        for y in 1..=BOARD_SIZE {
+            for x in 1..=BOARD_SIZE {
+                if self.get_type(&Point::new(x, y)) == t {
+                    ret |= 1u64 << ((BOARD_SIZE - y) as u32 * BOARD_SIZE as u32 + x as u32 - 1);
+                }
+            }
+        }
+        ret
+    }
+
+    //the shape goes along: a shape which the transform changes becomes a mask
+    pub fn transform(&self, t: Transform) -> Field {
+        let shape = self.shape();
+        let mut mask = 0;
+        for p in shape.cells() {
+            let q = t.apply(&p);
+            mask |= 1u64 << ((BOARD_SIZE - q.y()) as u32 * BOARD_SIZE as u32 + q.x() as u32 - 1);
+        }
+        let mut ret = Field::with_shape(if mask == shape.mask() { shape } else { Shape::Mask(mask) });
+        for p in shape.cells() {
+            let bw = self.get_type(&p);
+            if bw != OccupyType::Empty {
+                ret.set_type(&t.apply(&p), bw);
+            }
+        }
+        ret
+    }
+
+    //the transform with the smallest bitboards and the transformed field, the same for all equivalent fields
+    pub fn canonical(&self) -> (Field, Transform) {
+        TRANSFORMS.iter()
+            .map(|t| (self.transform(*t), *t))
+            .min_by_key(|(f, _)| (f.bitboards(), f.bitboard(OccupyType::Blocked)))
+            .unwrap()
+    }
+
+    //hash of the canonical form, the side to move is not a part of the field
+    pub fn canonical_hash(&self) -> u64 {
+        let canonical = self.canonical().0;
+        let (black, white) = canonical.bitboards();
+        let hash = mix(mix(black) ^ white);
+        //the same as without the blocked cells for the usual boards
+        match canonical.bitboard(OccupyType::Blocked) {
+            0 => hash,
+            blocked => mix(hash ^ blocked),
+        }
+    }
+}
+
+
+#[test]
+fn symmetry_point_test() {
+    let p = Point::new(2, 7);
+    assert_eq!(Point::new(7, 7), Transform::Rotate90.apply(&p));
+    assert_eq!(Point::new(2, 2), Transform::FlipVertical.apply(&p));
+    assert_eq!(Point::new(7, 2), Transform::Rotate180.apply(&p));
+    assert_eq!(Point::new(2, 7), Transform::FlipDiagonal.apply(&p));
+    assert_eq!(Point::new(7, 2), Transform::FlipAntiDiagonal.apply(&p));
+    for t in TRANSFORMS {
+        assert_eq!(p, t.inverse().apply(&t.apply(&p)));
+    }
+    assert_eq!(Transform::FlipHorizontal.apply(&p), map_point(&Transform::Rotate90.apply(&p), Transform::Rotate90, Transform::FlipHorizontal));
+    //four rotations are the identity
+    let r = (0..4).fold(p, |q, _| Transform::Rotate90.apply(&q));
+    assert_eq!(p, r);
+}
+
+#[test]
+fn symmetry_canonical_test() {
+    let mut f = Field::new();
+    f.init();
+    f.move_in_game(&Point::new(5, 6), OccupyType::White);
+    let hash = f.canonical_hash();
+    let (canonical, _) = f.canonical();
+    for t in TRANSFORMS {
+        let g = f.transform(t);
+        assert_eq!(hash, g.canonical_hash());
+        let (c, back) = g.canonical();
+        assert_eq!(canonical.serialize(), c.serialize());
+        assert_eq!(c.serialize(), g.transform(back).serialize());
+    }
+
+    //a move on the canonical board played back on the real one
+    let (c, t) = f.canonical();
+    for (p, _) in c.get_list_of_moves(OccupyType::Black) {
+        assert!(f.is_valid_move(&t.inverse().apply(&p), OccupyType::Black).0);
+    }
+
+    let mut g = Field::new();
+    g.init();
+    assert_ne!(hash, g.canonical_hash());
+    assert_eq!((1 << 28 | 1 << 35, 1 << 27 | 1 << 36), g.bitboards());
+
+    //holes are a part of the position
+    let plain = g.canonical_hash();
+    g.set_type(&Point::new(1, 8), OccupyType::Blocked);
+    assert_ne!(plain, g.canonical_hash());
+    assert_eq!(g.canonical_hash(), g.transform(Transform::Rotate90).canonical_hash());
+}
+
+#[test]
+fn symmetry_shape_test() {
+    let mut f = Field::with_shape(Shape::Octagon(2));
+    f.init();
+    for t in TRANSFORMS {
+        let g = f.transform(t);
+        assert_eq!(Shape::Octagon(2), g.shape());
+        assert_eq!(f.canonical_hash(), g.canonical_hash());
+    }
+
+    //a rectangle turned on its side
+    let mut f = Field::with_shape(Shape::Rectangle(4, 6));
+    f.init();
+    let g = f.transform(Transform::Rotate90);
+    assert_eq!(Shape::Rectangle(6, 4).mask(), g.shape().mask());
+    assert!(g.check_point(2, 3) && !g.check_point(2, 2) && !g.check_point(1, 3));
+    assert_eq!(f.get_score_wb(), g.get_score_wb());
+    assert_eq!(f.serialize(), g.transform(Transform::Rotate270).serialize());
+    assert_eq!(f.shape().mask(), g.transform(Transform::Rotate270).shape().mask());
+}
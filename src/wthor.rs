@@ -0,0 +1,359 @@
+/*
+WTHOR game database (.wtb), all numbers little-endian
+
+  header, 16 bytes
+    0     century of the file creation (20)
+    1     year in the century (24)
+    2, 3  month, day
+    4..8  u32 number of games
+    8..10 u16 number of records (0 in .wtb files)
+    10..12 u16 year of the games
+    12    board size (0 or 8)
+    13    game type (0 normal)
+    14    depth of the theoretical score
+    15    reserved
+
+  game, 68 bytes
+    0..2  u16 tournament number
+    2..4  u16 black player number
+    4..6  u16 white player number
+    6     black discs at the end of the game
+    7     theoretical black score
+    8..68 moves, `10 * row + column` with a1 = 11 (the top left corner), 0 after the last move
+
+Black moves first from the standard position, passes are not stored. The player and tournament names
+are in WTHOR.JOU (20-byte records) and WTHOR.TRN (26-byte records) with the same 16-byte header.
+*/
+use std::io::{self, Read, Write};
+
+use crate::point::{Point, BOARD_SIZE};
+use crate::occupytype::OccupyType;
+use crate::field::{Field, FieldAction};
+use crate::game::possible_movement;
+
+
+pub const HEADER_SIZE: usize = 16;
+pub const GAME_SIZE: usize = 68;
+pub const MAX_MOVES: usize = 60;
+const PLAYER_NAME_SIZE: usize = 20;
+const TOURNAMENT_NAME_SIZE: usize = 26;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WthorHeader {
+    //date of the file creation
+    pub created_year: u16,
+    pub created_month: u8,
+    pub created_day: u8,
+    pub games: u32,
+    pub records: u16,
+    //year of the games
+    pub year: u16,
+    pub board_size: u8,
+    pub game_type: u8,
+    pub depth: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WthorGame {
+    pub tournament: u16,
+    pub black: u16,
+    pub white: u16,
+    //black discs at the end of the game and the theoretical score
+    pub score: u8,
+    pub theoretical: u8,
+    pub moves: Vec<Point>,
+}
+
+
+fn u16_at(data: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([data[i], data[i + 1]])
+}
+
+fn encode_move(p: &Point) -> u8 {
+    10 * (BOARD_SIZE + 1 - p.y()) + p.x()
+}
+
+fn decode_move(b: u8) -> Option<Point> {
+    let (row, column) = ((b / 10) as i8, (b % 10) as i8);
+    let y = BOARD_SIZE as i8 + 1 - row;
+    if !Point::check_point(column, y) {
+        return None;
+    }
+    Some(Point::new(column as u8, y as u8))
+}
+
+fn mirror(p: &Point) -> Point {
+    Point::new(BOARD_SIZE + 1 - p.x(), p.y())
+}
+
+
+impl WthorHeader {
+    pub fn new(year: u16) -> Self {
+        WthorHeader {
+            created_year: year,
+            created_month: 1,
+            created_day: 1,
+            games: 0,
+            records: 0,
+            year,
+            board_size: BOARD_SIZE,
+            game_type: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<WthorHeader, String> {
+        if data.len() < HEADER_SIZE {
+            return Err("the header is too short".to_string());
+        }
+        let header = WthorHeader {
+            created_year: data[0] as u16 * 100 + data[1] as u16,
+            created_month: data[2],
+            created_day: data[3],
+            games: u32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+            records: u16_at(data, 8),
+            year: u16_at(data, 10),
+            board_size: data[12],
+            game_type: data[13],
+            depth: data[14],
+        };
+        if header.board_size != 0 && header.board_size != BOARD_SIZE {
+            return Err(format!("unsupported board size {}", header.board_size));
+        }
+        Ok(header)
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut data = [0; HEADER_SIZE];
+        data[0] = (self.created_year / 100) as u8;
+        data[1] = (self.created_year % 100) as u8;
+        data[2] = self.created_month;
+        data[3] = self.created_day;
+        data[4..8].copy_from_slice(&self.games.to_le_bytes());
+        data[8..10].copy_from_slice(&self.records.to_le_bytes());
+        data[10..12].copy_from_slice(&self.year.to_le_bytes());
+        data[12] = self.board_size;
+        data[13] = self.game_type;
+        data[14] = self.depth;
+        data
+    }
+}
+
+
+impl WthorGame {
+    //the moves are checked by replaying, the score is counted
+    pub fn new(tournament: u16, black: u16, white: u16, moves: &[Point]) -> Result<WthorGame, String> {
+        let mut game = WthorGame { tournament, black, white, score: 0, theoretical: 0, moves: moves.to_vec() };
+        let f = game.replay()?;
+        let (_, b) = f.get_score_wb();
+        game.score = b;
+        game.theoretical = b;
+        Ok(game)
+    }
+
+    //a move history with passes (None) from the standard position, e.g. `SavedGame::moves`.
+    //WTHOR games start with black; a game started by white is stored mirrored with the colours swapped,
+    //which is the same standard position.
+    pub fn from_history(moves: &[Option<Point>], first: OccupyType) -> Result<WthorGame, String> {
+        let moves: Vec<Point> = moves.iter().flatten()
+            .map(|p| if first == OccupyType::White { mirror(p) } else { *p })
+            .collect();
+        WthorGame::new(0, 0, 0, &moves)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<WthorGame, String> {
+        if data.len() < GAME_SIZE {
+            return Err("the game record is too short".to_string());
+        }
+        let mut moves = vec![];
+        for &b in data[8..GAME_SIZE].iter().take_while(|b| **b != 0) {
+            moves.push(decode_move(b).ok_or_else(|| format!("wrong move code {}", b))?);
+        }
+        Ok(WthorGame {
+            tournament: u16_at(data, 0),
+            black: u16_at(data, 2),
+            white: u16_at(data, 4),
+            score: data[6],
+            theoretical: data[7],
+            moves,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; GAME_SIZE] {
+        let mut data = [0; GAME_SIZE];
+        data[0..2].copy_from_slice(&self.tournament.to_le_bytes());
+        data[2..4].copy_from_slice(&self.black.to_le_bytes());
+        data[4..6].copy_from_slice(&self.white.to_le_bytes());
+        data[6] = self.score;
+        data[7] = self.theoretical;
+        for (i, p) in self.moves.iter().take(MAX_MOVES).enumerate() {
+            data[8 + i] = encode_move(p);
+        }
+        data
+    }
+
+    //the moves with the side that played them, the passes are skipped over
+    pub fn history(&self) -> Result<Vec<(Point, OccupyType)>, String> {
+        let mut f = Field::new();
+        f.init();
+        let mut bw = OccupyType::Black;
+        let mut ret = vec![];
+        for (i, p) in self.moves.iter().enumerate() {
+            if !possible_movement(bw, &f) {
+                bw = OccupyType::get_opposite_type(bw);
+            }
+            let (valid, _) = f.is_valid_move(p, bw);
+            if !valid {
+                return Err(format!("move {} {} is not valid for {}", i + 1, p.to_algebraic(), bw));
+            }
+            f.move_in_game(p, bw);
+            ret.push((*p, bw));
+            bw = OccupyType::get_opposite_type(bw);
+        }
+        Ok(ret)
+    }
+
+    //the final position, an error on an illegal move
+    pub fn replay(&self) -> Result<Field, String> {
+        let mut f = Field::new();
+        f.init();
+        for (p, bw) in self.history()? {
+            f.move_in_game(&p, bw);
+        }
+        Ok(f)
+    }
+}
+
+
+//streams the games of a .wtb file, every game is validated by replaying it
+pub struct WthorReader<R: Read> {
+    reader: R,
+    pub header: WthorHeader,
+    index: u32,
+}
+
+impl<R: Read> WthorReader<R> {
+    pub fn new(mut reader: R) -> Result<WthorReader<R>, String> {
+        let mut data = [0; HEADER_SIZE];
+        reader.read_exact(&mut data).map_err(|e| format!("cannot read the header: {}", e))?;
+        let header = WthorHeader::from_bytes(&data)?;
+        Ok(WthorReader { reader, header, index: 0 })
+    }
+}
+
+impl<R: Read> Iterator for WthorReader<R> {
+    type Item = Result<WthorGame, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.header.games {
+            return None;
+        }
+        self.index += 1;
+        let n = self.index;
+        let mut data = [0; GAME_SIZE];
+        if let Err(e) = self.reader.read_exact(&mut data) {
+            //no more games after a truncated file
+            self.index = self.header.games;
+            return Some(Err(format!("game {}: {}", n, e)));
+        }
+        let game = WthorGame::from_bytes(&data)
+            .and_then(|game| game.replay().map(|_| game))
+            .map_err(|e| format!("game {}: {}", n, e));
+        Some(game)
+    }
+}
+
+
+//the number of games in the header is taken from `games`
+pub fn write<W: Write>(out: &mut W, header: &WthorHeader, games: &[WthorGame]) -> io::Result<()> {
+    let mut header = header.clone();
+    header.games = games.len() as u32;
+    out.write_all(&header.to_bytes())?;
+    for game in games {
+        out.write_all(&game.to_bytes())?;
+    }
+    Ok(())
+}
+
+
+fn read_names(data: &[u8], size: usize) -> Result<Vec<String>, String> {
+    let header = WthorHeader::from_bytes(data)?;
+    let body = &data[HEADER_SIZE..];
+    let count = header.records as usize;
+    if body.len() < count * size {
+        return Err(format!("expected {} names", count));
+    }
+    //ISO-8859-1, padded with zeros
+    Ok(body.chunks(size).take(count)
+        .map(|name| name.iter().take_while(|b| **b != 0).map(|b| *b as char).collect::<String>().trim().to_string())
+        .collect())
+}
+
+//WTHOR.JOU, the index is the player number
+pub fn read_players(data: &[u8]) -> Result<Vec<String>, String> {
+    read_names(data, PLAYER_NAME_SIZE)
+}
+
+//WTHOR.TRN, the index is the tournament number
+pub fn read_tournaments(data: &[u8]) -> Result<Vec<String>, String> {
+    read_names(data, TOURNAMENT_NAME_SIZE)
+}
+
+
+#[test]
+fn wthor_round_trip_test() {
+    //f5 d6 c3 d3 c4
+    let moves: Vec<Point> = ["f5", "d6", "c3", "d3", "c4"].iter().map(|m| Point::from_algebraic(m).unwrap()).collect();
+    let game = WthorGame::new(3, 10, 20, &moves).unwrap();
+    assert_eq!(56, game.to_bytes()[8]);
+    let mut data = vec![];
+    write(&mut data, &WthorHeader::new(2024), &[game.clone(), game.clone()]).unwrap();
+    assert_eq!(HEADER_SIZE + 2 * GAME_SIZE, data.len());
+
+    let reader = WthorReader::new(&data[..]).unwrap();
+    assert_eq!(2, reader.header.games);
+    assert_eq!(2024, reader.header.year);
+    let games: Vec<WthorGame> = reader.map(|g| g.unwrap()).collect();
+    assert_eq!(vec![game.clone(), game], games);
+    let (w, b) = games[0].replay().unwrap().get_score_wb();
+    assert_eq!((3, 6), (w, b));
+    assert_eq!(6, games[0].score);
+}
+
+#[test]
+fn wthor_invalid_game_test() {
+    let mut data = vec![];
+    let game = WthorGame { tournament: 0, black: 0, white: 0, score: 0, theoretical: 0,
+        moves: vec![Point::from_algebraic("a1").unwrap()] };
+    write(&mut data, &WthorHeader::new(2024), &[game]).unwrap();
+    let mut reader = WthorReader::new(&data[..]).unwrap();
+    assert_eq!(Some(Err("game 1: move 1 a1 is not valid for #".to_string())), reader.next());
+    assert_eq!(None, reader.next());
+
+    //the header promises more games than there are
+    data[4] = 2;
+    let results: Vec<_> = WthorReader::new(&data[..]).unwrap().collect();
+    assert_eq!(2, results.len());
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn wthor_history_test() {
+    //white moves first in the interactive game
+    let first = Point::new(5, 6);
+    let mut f = Field::new();
+    f.init();
+    assert!(f.is_valid_move(&first, OccupyType::White).0);
+    let game = WthorGame::from_history(&[Some(first)], OccupyType::White).unwrap();
+    assert_eq!(vec![Point::new(4, 6)], game.moves);
+
+    let mut names = WthorHeader::new(2024);
+    names.records = 2;
+    let mut data = names.to_bytes().to_vec();
+    data.extend(b"Doe John\0\0\0\0\0\0\0\0\0\0\0\0");
+    data.extend([b'a'; PLAYER_NAME_SIZE]);
+    let players = read_players(&data).unwrap();
+    assert_eq!("Doe John", players[0]);
+    assert_eq!(PLAYER_NAME_SIZE, players[1].len());
+}
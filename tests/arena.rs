@@ -0,0 +1,100 @@
+//matches between two rustversi processes over the engine protocol
+use rustversi::arena::{self, EngineProcess, MatchSettings};
+use rustversi::clock::TimeControl;
+use rustversi::occupytype::OccupyType;
+use rustversi::record::GameRecord;
+use rustversi::rules::Rules;
+
+
+fn engine(args: &str) -> EngineProcess {
+    EngineProcess::spawn(&format!("{} engine {}", env!("CARGO_BIN_EXE_rustversi"), args)).unwrap()
+}
+
+#[test]
+fn arena_match_test() {
+    let mut first = engine("search:2");
+    let mut second = engine("random");
+    assert_eq!("rustversi search:2", first.name());
+    let settings = MatchSettings { games: 2, time: TimeControl::from_string("30s").unwrap(), rules: Rules::new() };
+    let mut rounds = vec![];
+    let results = arena::play_match(&mut first, &mut second, &settings, |round, _| rounds.push(round));
+    assert_eq!(vec![1, 2], rounds);
+    for (i, result) in results.iter().enumerate() {
+        assert!(result.termination.is_none());
+        //the record is a complete game
+        let record = GameRecord::from_string(&result.record.to_string()).unwrap();
+        let (f, _) = record.replay().unwrap();
+        assert_eq!(Some(GameRecord::result_of(&f).as_str()), record.tag("Result"));
+        assert_eq!(Some((i + 1).to_string().as_str()), record.tag("Round"));
+        assert!(record.moves.iter().all(|m| m.mv.is_none() || m.time_ms.is_some()));
+    }
+    assert_eq!(Some("rustversi random"), results[1].record.tag("Black"));
+    let points = arena::first_points(&results);
+    assert!((0.0..=2.0).contains(&points));
+}
+
+#[test]
+fn arena_rules_test() {
+    let mut black = engine("greedy");
+    let mut white = engine("corner");
+    let settings = MatchSettings { games: 1, rules: Rules::from_string("parallel").unwrap(), ..Default::default() };
+    let result = arena::play_game(&mut black, &mut white, &settings, 1);
+    assert!(result.termination.is_none());
+    assert!(result.record.tag("Setup").is_some());
+    assert!(result.record.replay().is_ok());
+}
+
+#[test]
+fn arena_misere_test() {
+    let mut black = engine("search:2");
+    let mut white = engine("greedy");
+    let settings = MatchSettings { games: 1, rules: Rules::from_string("misere").unwrap(), ..Default::default() };
+    let result = arena::play_game(&mut black, &mut white, &settings, 1);
+    assert!(result.termination.is_none());
+    //the record names the same winner as the match
+    let record = GameRecord::from_string(&result.record.to_string()).unwrap();
+    let rules = record.rules().unwrap();
+    assert!(rules.misere);
+    let (f, _) = record.replay().unwrap();
+    assert_eq!(result.winner, rules.winner(&f));
+}
+
+#[test]
+fn arena_time_forfeit_test() {
+    //a search takes longer than a millisecond
+    let mut black = engine("search:3");
+    let mut white = engine("random");
+    let settings = MatchSettings { games: 1, time: TimeControl::SuddenDeath { main_ms: 1 }, rules: Rules::new() };
+    let result = arena::play_game(&mut black, &mut white, &settings, 1);
+    assert_eq!(Some(OccupyType::White), result.winner);
+    assert_eq!(Some("Black loses on time"), result.termination.as_deref());
+    assert_eq!(Some("*"), result.record.tag("Result"));
+    assert!(result.record.moves.is_empty());
+
+    //the late answer of black is skipped in the next game
+    let result = arena::play_game(&mut white, &mut black, &MatchSettings { games: 1, ..Default::default() }, 2);
+    assert!(result.termination.is_none(), "{:?}", result.termination);
+}
+
+#[test]
+fn arena_byo_yomi_test() {
+    //the main time is used up by the first move, the engines think in the periods
+    let mut black = engine("search:3");
+    let mut white = engine("search:3");
+    let settings = MatchSettings { games: 1, time: TimeControl::from_string("1ms/2x5s").unwrap(), rules: Rules::new() };
+    let result = arena::play_game(&mut black, &mut white, &settings, 1);
+    assert!(result.termination.is_none(), "{:?}", result.termination);
+    assert!(result.record.replay().is_ok());
+}
+
+#[test]
+fn arena_spawn_test() {
+    assert!(EngineProcess::spawn("").is_err());
+    assert!(EngineProcess::spawn("/nonexistent/engine").is_err());
+    //the program may exit before the handshake is written
+    let command = format!("{} show start", env!("CARGO_BIN_EXE_rustversi"));
+    let err = EngineProcess::spawn(&command).err().unwrap();
+    assert!(err.starts_with(&format!("'{}' does not speak the protocol", command)), "{}", err);
+    let err = EngineProcess::spawn("true").err().unwrap();
+    assert!(err.starts_with("'true' does not speak the protocol"), "{}", err);
+}
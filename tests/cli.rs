@@ -0,0 +1,142 @@
+//the subcommands of the rustversi binary: their output, exit codes and errors
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+
+const PUZZLE: &str = "-XXXXX--OOOXXX--OOOXXXXXOOOXXXOO-OOXOXXOOXOOXOXOO-OOOXXOOOOXXXX- X; win; h8 a5 a1; Black to move and win";
+
+fn rustversi(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rustversi")).args(args).output().unwrap()
+}
+
+fn rustversi_with_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rustversi")).args(args)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+//a file of the temp dir, unique for the test
+fn temp_file(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(format!("rustversi-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn cli_apply_test() {
+    let output = rustversi(&["apply", "start", "--to-move", "black", "4,6", "3,4"]);
+    assert_eq!(Some(0), output.status.code());
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(2, lines.len());
+    assert_eq!(64, lines[0].len());
+    assert_eq!("#", lines[1]);
+
+    //white moves first in the interactive game
+    let output = rustversi(&["apply", "start", "--to-move", "white", "3,4"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!(Some("#"), stdout(&output).lines().nth(1));
+
+    //the side is required
+    let output = rustversi(&["apply", "start", "4,6"]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(stderr(&output).starts_with("error: --to-move <color> is missing\nusage:"));
+    assert_eq!(Some(2), rustversi(&["apply", "start", "--to-move"]).status.code());
+    assert_eq!(Some(2), rustversi(&["apply"]).status.code());
+
+    let output = rustversi(&["apply", "start", "--to-move", "black", "1,1"]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("error: (1,1) is not a valid move for #\n", stderr(&output));
+    let output = rustversi(&["apply", "start", "--to-move", "black", "pass"]);
+    assert_eq!("error: # cannot pass, it has a legal move\n", stderr(&output));
+    assert_eq!(Some(1), rustversi(&["apply", "start", "--to-move", "grey"]).status.code());
+    assert_eq!(Some(1), rustversi(&["apply", "start", "--to-move", "black", "z"]).status.code());
+}
+
+#[test]
+fn cli_board_commands_test() {
+    let output = rustversi(&["legal", "start", "black"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("3 5 1\n4 6 1\n5 3 1\n6 4 1\n", stdout(&output));
+    let output = rustversi(&["show", "start"]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).ends_with("Score # vs O -- 2:2\n"));
+    let output = rustversi(&["bestmove", "start", "black", "search:2"]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(["3 5\n", "4 6\n", "5 3\n", "6 4\n"].contains(&stdout(&output).as_str()), "{}", stdout(&output));
+
+    let output = rustversi(&["show", "nonsense"]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).starts_with("error: wrong board"));
+    assert_eq!(Some(1), rustversi(&["show", "start", "--style", "fancy"]).status.code());
+    assert_eq!(Some(1), rustversi(&["bestmove", "start", "black", "genius"]).status.code());
+    assert_eq!(Some(2), rustversi(&["legal", "start"]).status.code());
+}
+
+#[test]
+fn cli_usage_test() {
+    let output = rustversi(&["help"]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).starts_with("usage:"));
+    let output = rustversi(&["frobnicate"]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(stderr(&output).starts_with("error: unknown command 'frobnicate'"));
+
+    //an unknown flag is not taken for an option without its value
+    let db = std::env::temp_dir().join(format!("rustversi-cli-{}-empty.db", std::process::id()));
+    let db = db.to_str().unwrap();
+    for args in [&["render", "start", "--bogus"][..], &["db", db, "query", "--bogus"], &["match", "a", "b", "--bogus"]] {
+        let output = rustversi(args);
+        assert_eq!(Some(2), output.status.code());
+        assert!(stderr(&output).starts_with("error: unknown option '--bogus'"), "{}", stderr(&output));
+    }
+    assert!(stderr(&rustversi(&["render", "start", "--size"])).starts_with("error: --size needs a value"));
+    let output = rustversi(&["db", db, "query", "--discs", "20-10"]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("error: wrong range '20-10', 20 is more than 10\n", stderr(&output));
+    let output = rustversi(&["--mode", "hvh", "--bogus"]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(stderr(&output).starts_with("error: unknown option '--bogus'"), "{}", stderr(&output));
+}
+
+#[test]
+fn cli_puzzle_test() {
+    let good = temp_file("good.txt", &format!("% a sound puzzle\n{}\n", PUZZLE));
+    let output = rustversi(&["puzzle", &good, "--verify"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("1 ok\n", stdout(&output));
+
+    //a5 is the reply, it does not win for the player
+    let bad = temp_file("bad.txt", &PUZZLE.replace("h8 a5 a1", "a5"));
+    let output = rustversi(&["puzzle", &bad, "--verify"]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("1 move 1 a5 does not win\n", stdout(&output));
+    assert_eq!("error: 1 of 1 puzzles are not sound\n", stderr(&output));
+
+    let output = rustversi_with_input(&["puzzle", &good], "h8\na1\n");
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).ends_with("Solved 1 of 1\n"), "{}", stdout(&output));
+    let output = rustversi_with_input(&["puzzle", &good], "skip\n");
+    assert!(stdout(&output).ends_with("Solved 0 of 1\n"), "{}", stdout(&output));
+
+    assert_eq!(Some(1), rustversi(&["puzzle", &good, "--number", "2"]).status.code());
+    assert_eq!(Some(1), rustversi(&["puzzle", "/nonexistent/puzzles.txt"]).status.code());
+    let output = rustversi(&["puzzle", &good, "--bogus"]);
+    assert!(stderr(&output).starts_with("error: unknown option '--bogus'"), "{}", stderr(&output));
+    let broken = temp_file("broken.txt", "not a puzzle\n");
+    let output = rustversi(&["puzzle", &broken]);
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).starts_with(&format!("error: {}:", broken)));
+    for path in [good, bad, broken] {
+        let _ = std::fs::remove_file(path);
+    }
+}
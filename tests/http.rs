@@ -0,0 +1,56 @@
+//the analysis service on localhost, through the public `HttpServer`
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use rustversi::http::HttpServer;
+
+
+const START: &str = r#"{"rows": ["........", "........", "........", "...#O...", "...O#...", "........", "........", "........"]}"#;
+
+fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           method, path, body.len(), body).unwrap();
+    let mut answer = String::new();
+    stream.read_to_string(&mut answer).unwrap();
+    answer
+}
+
+#[test]
+fn http_localhost_test() {
+    let server = HttpServer::bind("127.0.0.1:0", 2).unwrap();
+    let addr = server.local_addr().unwrap();
+    std::thread::spawn(move || server.run());
+    let position = format!(r#"{{"board": {}, "to_move": "white"}}"#, START);
+    //requests at the same time for the pool
+    let answers: Vec<_> = (0..4).map(|_| {
+        let position = position.clone();
+        std::thread::spawn(move || request(addr, "POST", "/moves", &position))
+    }).collect();
+    for answer in answers {
+        let answer = answer.join().unwrap();
+        assert!(answer.starts_with("HTTP/1.1 200"), "{}", answer);
+        assert!(answer.contains("application/json"));
+        assert!(answer.contains(r#""flips":1"#));
+    }
+    assert!(request(addr, "POST", "/best?engine=x", &position).contains(r#"{"move":{"x":"#));
+    assert!(request(addr, "GET", "/moves", "").starts_with("HTTP/1.1 405"));
+    assert!(request(addr, "POST", "/moves", "[").starts_with("HTTP/1.1 400"));
+    assert!(request(addr, "POST", "/nothing", &position).starts_with("HTTP/1.1 404"));
+}
+
+#[test]
+fn http_options_test() {
+    let exe = env!("CARGO_BIN_EXE_rustversi-http");
+    let run = |args: &[&str]| std::process::Command::new(exe).args(args).output().unwrap();
+    let help = run(&["--help"]);
+    assert!(help.status.success());
+    assert!(String::from_utf8_lossy(&help.stdout).starts_with("usage: rustversi-http"));
+    let unknown = run(&["--port"]);
+    assert_eq!(Some(2), unknown.status.code());
+    assert!(String::from_utf8_lossy(&unknown.stderr).starts_with("error: unknown option '--port'"));
+    let missing = run(&["--threads"]);
+    assert!(String::from_utf8_lossy(&missing.stderr).starts_with("error: --threads needs a value"));
+    let zero = run(&["--threads", "0"]);
+    assert!(String::from_utf8_lossy(&zero.stderr).starts_with("error: wrong number of threads '0'"));
+}